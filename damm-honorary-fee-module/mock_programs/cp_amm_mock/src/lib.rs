@@ -0,0 +1,391 @@
+//! Local cp-amm stand-in for testing DAMM fee distribution
+//!
+//! This program is deployed at the DAMM v2 cp-amm program ID in local tests and
+//! mirrors the account ordering of the cp-amm instructions the honorary fee
+//! module invokes via CPI. Swap activity is simulated with `accrue_position_fee`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface;
+
+declare_id!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG");
+
+/// Seed of the pool authority PDA that owns the pool vaults
+pub const POOL_AUTHORITY_SEED: &[u8] = b"pool_authority";
+
+/// Seed of the event authority PDA used by cp-amm's event CPI
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+#[program]
+pub mod cp_amm_mock {
+    use super::*;
+
+    /// Create a pool for a token pair with program-owned vaults
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        sqrt_min_price: u128,
+        sqrt_max_price: u128,
+        sqrt_price: u128,
+        collect_fee_mode: u8,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_init()?;
+        pool.token_a_mint = ctx.accounts.token_a_mint.key();
+        pool.token_b_mint = ctx.accounts.token_b_mint.key();
+        pool.token_a_vault = ctx.accounts.token_a_vault.key();
+        pool.token_b_vault = ctx.accounts.token_b_vault.key();
+        pool.sqrt_min_price = sqrt_min_price;
+        pool.sqrt_max_price = sqrt_max_price;
+        pool.sqrt_price = sqrt_price;
+        pool.collect_fee_mode = collect_fee_mode;
+        Ok(())
+    }
+
+    /// Create a position in a pool, owned by whoever holds its NFT
+    pub fn create_position(ctx: Context<CreatePosition>) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.pool = ctx.accounts.pool.key();
+        position.nft_mint = ctx.accounts.position_nft_mint.key();
+        position.fee_a_pending = 0;
+        position.fee_b_pending = 0;
+
+        let bump = ctx.bumps.pool_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_AUTHORITY_SEED, &[bump]]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::MintTo {
+                    mint: ctx.accounts.position_nft_mint.to_account_info(),
+                    to: ctx.accounts.position_nft_account.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )
+    }
+
+    /// Deposit fees into the pool vaults and credit them to a position (for testing)
+    pub fn accrue_position_fee(
+        ctx: Context<AccruePositionFee>,
+        fee_a: u64,
+        fee_b: u64,
+    ) -> Result<()> {
+        if fee_a > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.funder_token_a.to_account_info(),
+                        to: ctx.accounts.token_a_vault.to_account_info(),
+                        authority: ctx.accounts.funder.to_account_info(),
+                    },
+                ),
+                fee_a,
+            )?;
+        }
+        if fee_b > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.funder_token_b.to_account_info(),
+                        to: ctx.accounts.token_b_vault.to_account_info(),
+                        authority: ctx.accounts.funder.to_account_info(),
+                    },
+                ),
+                fee_b,
+            )?;
+        }
+
+        let position = &mut ctx.accounts.position;
+        position.fee_a_pending = position
+            .fee_a_pending
+            .checked_add(fee_a)
+            .ok_or(CpAmmMockError::MathOverflow)?;
+        position.fee_b_pending = position
+            .fee_b_pending
+            .checked_add(fee_b)
+            .ok_or(CpAmmMockError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Claim a position's pending fees to the owner's token accounts
+    pub fn claim_position_fee(ctx: Context<ClaimPositionFee>) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        let fee_a = position.fee_a_pending;
+        let fee_b = position.fee_b_pending;
+        position.fee_a_pending = 0;
+        position.fee_b_pending = 0;
+
+        let bump = ctx.bumps.pool_authority;
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_AUTHORITY_SEED, &[bump]]];
+        if fee_a > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_a_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.token_a_vault.to_account_info(),
+                        to: ctx.accounts.token_a_account.to_account_info(),
+                        authority: ctx.accounts.pool_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_a,
+            )?;
+        }
+        if fee_b > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_b_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.token_b_vault.to_account_info(),
+                        to: ctx.accounts.token_b_account.to_account_info(),
+                        authority: ctx.accounts.pool_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_b,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    /// The pool account
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<Pool>())]
+    pub pool: AccountLoader<'info, Pool>,
+
+    /// CHECK: PDA owning the pool vaults
+    #[account(seeds = [POOL_AUTHORITY_SEED], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+
+    /// Token A vault
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"token_vault", token_a_mint.key().as_ref(), pool.key().as_ref()],
+        bump,
+        token::mint = token_a_mint,
+        token::authority = pool_authority,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    /// Token B vault
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"token_vault", token_b_mint.key().as_ref(), pool.key().as_ref()],
+        bump,
+        token::mint = token_b_mint,
+        token::authority = pool_authority,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePosition<'info> {
+    /// CHECK: Receives the position NFT
+    pub owner: UncheckedAccount<'info>,
+
+    /// The position NFT mint (Token-2022, as in cp-amm)
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = pool_authority,
+        mint::token_program = token_program,
+    )]
+    pub position_nft_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// The token account holding the position NFT
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"position_nft_account", position_nft_mint.key().as_ref()],
+        bump,
+        token::mint = position_nft_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub position_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(mut)]
+    pub pool: AccountLoader<'info, Pool>,
+
+    /// The position account
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"position", position_nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// CHECK: PDA minting the position NFT
+    #[account(seeds = [POOL_AUTHORITY_SEED], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: cp-amm event authority
+    #[account(seeds = [EVENT_AUTHORITY_SEED], bump)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    pub program: Program<'info, program::CpAmmMock>,
+}
+
+#[derive(Accounts)]
+pub struct AccruePositionFee<'info> {
+    #[account(has_one = token_a_vault, has_one = token_b_vault)]
+    pub pool: AccountLoader<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub position: Account<'info, Position>,
+
+    #[account(mut)]
+    pub token_a_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub funder_token_a: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub funder_token_b: Account<'info, TokenAccount>,
+
+    pub funder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPositionFee<'info> {
+    /// CHECK: PDA owning the pool vaults
+    #[account(seeds = [POOL_AUTHORITY_SEED], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        has_one = token_a_vault,
+        has_one = token_b_vault,
+        has_one = token_a_mint,
+        has_one = token_b_mint,
+    )]
+    pub pool: AccountLoader<'info, Pool>,
+
+    #[account(mut, has_one = pool)]
+    pub position: Account<'info, Position>,
+
+    /// The owner's token A account
+    #[account(mut, token::mint = token_a_mint)]
+    pub token_a_account: Account<'info, TokenAccount>,
+
+    /// The owner's token B account
+    #[account(mut, token::mint = token_b_mint)]
+    pub token_b_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub token_a_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+
+    /// The token account holding the position NFT
+    #[account(
+        constraint = position_nft_account.mint == position.nft_mint @ CpAmmMockError::InvalidPositionOwner,
+        constraint = position_nft_account.amount == 1 @ CpAmmMockError::InvalidPositionOwner,
+        token::authority = owner,
+    )]
+    pub position_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Owner of the position
+    pub owner: Signer<'info>,
+
+    pub token_a_program: Program<'info, Token>,
+    pub token_b_program: Program<'info, Token>,
+
+    /// CHECK: cp-amm event authority
+    #[account(seeds = [EVENT_AUTHORITY_SEED], bump)]
+    pub event_authority: UncheckedAccount<'info>,
+
+    pub program: Program<'info, program::CpAmmMock>,
+}
+
+/// Pool state, laid out as the zero-copy cp-amm `Pool` (fields the mock does
+/// not use are kept as opaque bytes of the same size)
+///
+/// Packed so it loads from account data on hosts where `u128` is 16-byte
+/// aligned; the layout has no padding, so the offsets match cp-amm's.
+#[account(zero_copy(unsafe))]
+pub struct Pool {
+    pub pool_fees: [u64; 20],
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub whitelisted_vault: Pubkey,
+    pub partner: Pubkey,
+    pub liquidity: u128,
+    pub _padding: u128,
+    pub protocol_a_fee: u64,
+    pub protocol_b_fee: u64,
+    pub partner_a_fee: u64,
+    pub partner_b_fee: u64,
+    /// Price range shared by all positions (Q64.64)
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
+    /// Current sqrt price (token B per token A, Q64.64)
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    pub activation_type: u8,
+    pub pool_status: u8,
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    /// Token(s) swap fees are charged in: 0 = both tokens, 1 = token B only
+    pub collect_fee_mode: u8,
+    pub pool_type: u8,
+    pub version: u8,
+    pub _padding_0: u8,
+    pub fee_a_per_liquidity: [u8; 32],
+    pub fee_b_per_liquidity: [u8; 32],
+    pub permanent_lock_liquidity: u128,
+    pub metrics: [u64; 10],
+    pub creator: Pubkey,
+    pub _padding_1: [u64; 6],
+    pub reward_infos: [[u64; 24]; 2],
+}
+
+/// Position state
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub pool: Pubkey,
+    pub nft_mint: Pubkey,
+    pub fee_a_pending: u64,
+    pub fee_b_pending: u64,
+}
+
+#[error_code]
+pub enum CpAmmMockError {
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Signer does not own the position")]
+    InvalidPositionOwner,
+}
@@ -2,10 +2,16 @@
 //!
 //! This program provides a simple interface to set and query locked amounts
 //! for stream pubkeys, simulating the Streamflow program's locked amount queries.
+//! It is deployed at the Streamflow program ID in local tests and can create
+//! stream accounts using the Streamflow contract layout.
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, CreateAccount};
 
-declare_id!("StreamMock11111111111111111111111111111111");
+declare_id!("strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m");
+
+/// Space allocated for a stream contract account (matches Streamflow)
+pub const CONTRACT_SPACE: usize = 1104;
 
 #[program]
 pub mod streamflow_mock {
@@ -34,6 +40,97 @@ pub mod streamflow_mock {
         }
         Ok(locked_account.locked_amount)
     }
+
+    /// Create a stream account with the Streamflow contract layout (for testing)
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        start_time: u64,
+        net_amount_deposited: u64,
+        period: u64,
+        amount_per_period: u64,
+        cliff_amount: u64,
+    ) -> Result<()> {
+        if period == 0 || amount_per_period == 0 || cliff_amount > net_amount_deposited {
+            return Err(StreamflowMockError::InvalidSchedule.into());
+        }
+
+        let rent = Rent::get()?;
+        system_program::create_account(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.sender.to_account_info(),
+                    to: ctx.accounts.stream.to_account_info(),
+                },
+            ),
+            rent.minimum_balance(CONTRACT_SPACE),
+            CONTRACT_SPACE as u64,
+            &ID,
+        )?;
+
+        // Streamflow derives the end time from the schedule
+        let streamed_after_cliff = net_amount_deposited - cliff_amount;
+        let periods = (streamed_after_cliff + amount_per_period - 1) / amount_per_period;
+        let end_time = start_time + periods * period;
+
+        let contract = Contract {
+            magic: 0,
+            version: 0,
+            created_at: Clock::get()?.unix_timestamp as u64,
+            amount_withdrawn: 0,
+            canceled_at: 0,
+            end_time,
+            last_withdrawn_at: 0,
+            sender: ctx.accounts.sender.key(),
+            sender_tokens: Pubkey::default(),
+            recipient: ctx.accounts.recipient.key(),
+            recipient_tokens: Pubkey::default(),
+            mint: ctx.accounts.mint.key(),
+            escrow_tokens: Pubkey::default(),
+            streamflow_treasury: Pubkey::default(),
+            streamflow_treasury_tokens: Pubkey::default(),
+            streamflow_fee_total: 0,
+            streamflow_fee_withdrawn: 0,
+            streamflow_fee_percent: 0.0,
+            partner: Pubkey::default(),
+            partner_tokens: Pubkey::default(),
+            partner_fee_total: 0,
+            partner_fee_withdrawn: 0,
+            partner_fee_percent: 0.0,
+            ix: CreateParams {
+                start_time,
+                net_amount_deposited,
+                period,
+                amount_per_period,
+                cliff: start_time,
+                cliff_amount,
+                cancelable_by_sender: false,
+                cancelable_by_recipient: false,
+                automatic_withdrawal: false,
+                transferable_by_sender: false,
+                transferable_by_recipient: false,
+                can_topup: false,
+                stream_name: [0; 64],
+                withdraw_frequency: period,
+            },
+        };
+
+        let mut data = ctx.accounts.stream.try_borrow_mut_data()?;
+        contract.serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    /// Mark a stream as canceled (for testing)
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        let mut data = ctx.accounts.stream.try_borrow_mut_data()?;
+        let mut contract = Contract::deserialize(&mut &data[..])?;
+        if contract.sender != ctx.accounts.sender.key() {
+            return Err(StreamflowMockError::StreamNotFound.into());
+        }
+        contract.canceled_at = Clock::get()?.unix_timestamp as u64;
+        contract.serialize(&mut &mut data[..])?;
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -50,6 +147,7 @@ pub struct SetLockedAmount<'info> {
     pub locked_account: Account<'info, LockedAmountAccount>,
 
     /// Authority to set locked amounts
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     /// System program
@@ -67,6 +165,84 @@ pub struct GetLockedAmount<'info> {
     pub locked_account: Account<'info, LockedAmountAccount>,
 }
 
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    /// The stream account (new keypair)
+    #[account(mut)]
+    pub stream: Signer<'info>,
+
+    /// The sender funding the stream
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Recipient of the vested tokens
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Mint of the vested token
+    pub mint: UncheckedAccount<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    /// CHECK: Stream contract account owned by this program
+    #[account(mut, owner = ID)]
+    pub stream: UncheckedAccount<'info>,
+
+    /// The stream sender
+    pub sender: Signer<'info>,
+}
+
+/// Stream creation parameters, laid out as in the Streamflow contract
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateParams {
+    pub start_time: u64,
+    pub net_amount_deposited: u64,
+    pub period: u64,
+    pub amount_per_period: u64,
+    pub cliff: u64,
+    pub cliff_amount: u64,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub automatic_withdrawal: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub can_topup: bool,
+    pub stream_name: [u8; 64],
+    pub withdraw_frequency: u64,
+}
+
+/// Stream contract account, laid out as in the Streamflow program (no discriminator)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Contract {
+    pub magic: u64,
+    pub version: u8,
+    pub created_at: u64,
+    pub amount_withdrawn: u64,
+    pub canceled_at: u64,
+    pub end_time: u64,
+    pub last_withdrawn_at: u64,
+    pub sender: Pubkey,
+    pub sender_tokens: Pubkey,
+    pub recipient: Pubkey,
+    pub recipient_tokens: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_tokens: Pubkey,
+    pub streamflow_treasury: Pubkey,
+    pub streamflow_treasury_tokens: Pubkey,
+    pub streamflow_fee_total: u64,
+    pub streamflow_fee_withdrawn: u64,
+    pub streamflow_fee_percent: f32,
+    pub partner: Pubkey,
+    pub partner_tokens: Pubkey,
+    pub partner_fee_total: u64,
+    pub partner_fee_withdrawn: u64,
+    pub partner_fee_percent: f32,
+    pub ix: CreateParams,
+}
+
 /// Storage account for locked amounts per stream
 #[account]
 pub struct LockedAmountAccount {
@@ -80,4 +256,6 @@ pub struct LockedAmountAccount {
 pub enum StreamflowMockError {
     #[msg("Stream not found")]
     StreamNotFound,
+    #[msg("Invalid vesting schedule")]
+    InvalidSchedule,
 }
\ No newline at end of file
@@ -0,0 +1,255 @@
+//! Minimal CPI client for the DAMM v2 cp-amm program
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    program::{invoke, invoke_signed},
+};
+use bytemuck::{Pod, Zeroable};
+
+use crate::errors::DammHonoraryFeeError;
+
+declare_id!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG");
+
+/// Seed of the cp-amm pool authority PDA
+pub const POOL_AUTHORITY_SEED: &[u8] = b"pool_authority";
+
+/// Seed of the cp-amm event authority PDA
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Lowest sqrt price supported by cp-amm (Q64.64)
+pub const MIN_SQRT_PRICE: u128 = 4295048016;
+
+/// Highest sqrt price supported by cp-amm (Q64.64)
+pub const MAX_SQRT_PRICE: u128 = 79226673521066979257578248091;
+
+/// `CollectFeeMode::BothToken`: swap fees are charged in the input token
+pub const COLLECT_FEE_MODE_BOTH_TOKEN: u8 = 0;
+
+/// `CollectFeeMode::OnlyB`: swap fees are always charged in token B
+pub const COLLECT_FEE_MODE_ONLY_B: u8 = 1;
+
+/// Anchor discriminator of the cp-amm `Pool` account
+const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+
+/// Anchor discriminator of the cp-amm `Position` account
+const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+
+/// Anchor sighash of `global:create_position`
+const CREATE_POSITION_DISCRIMINATOR: [u8; 8] = [48, 215, 197, 153, 96, 203, 180, 133];
+
+/// Anchor sighash of `global:claim_position_fee`
+const CLAIM_POSITION_FEE_DISCRIMINATOR: [u8; 8] = [180, 38, 154, 17, 133, 33, 162, 211];
+
+/// Program type for the cp-amm program
+#[derive(Clone)]
+pub struct CpAmm;
+
+impl Id for CpAmm {
+    fn id() -> Pubkey {
+        ID
+    }
+}
+
+/// Leading fields of a cp-amm pool account
+///
+/// cp-amm stores pools zero-copy (`repr(C)`), so the fields are read at
+/// their in-memory offsets after the discriminator rather than Borsh-decoded.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Pool {
+    /// Fee configuration (`PoolFeesStruct`), not read by this program
+    pub pool_fees: [u64; 20],
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub whitelisted_vault: Pubkey,
+    pub partner: Pubkey,
+    pub liquidity: u128,
+    pub _padding: u128,
+    pub protocol_a_fee: u64,
+    pub protocol_b_fee: u64,
+    pub partner_a_fee: u64,
+    pub partner_b_fee: u64,
+    /// Lower bound of the pool's price range (Q64.64), shared by all positions
+    pub sqrt_min_price: u128,
+    /// Upper bound of the pool's price range (Q64.64), shared by all positions
+    pub sqrt_max_price: u128,
+    /// Current sqrt price (token B per token A, Q64.64)
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    pub activation_type: u8,
+    pub pool_status: u8,
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    /// Token(s) swap fees are charged in (`COLLECT_FEE_MODE_*`)
+    pub collect_fee_mode: u8,
+    pub pool_type: u8,
+    pub version: u8,
+    pub _padding_0: u8,
+}
+
+impl Pool {
+    /// Decode a pool account, checking it is owned by cp-amm
+    pub fn try_from_account_info(pool: &AccountInfo) -> Result<Self> {
+        if pool.owner != &ID {
+            return Err(DammHonoraryFeeError::InvalidPoolAccount.into());
+        }
+
+        let data = pool.try_borrow_data()?;
+        let end = 8 + std::mem::size_of::<Self>();
+        if data.len() < end || data[..8] != POOL_DISCRIMINATOR {
+            return Err(DammHonoraryFeeError::InvalidPoolAccount.into());
+        }
+        bytemuck::try_pod_read_unaligned(&data[8..end])
+            .map_err(|_| DammHonoraryFeeError::InvalidPoolAccount.into())
+    }
+}
+
+/// Leading fields of a cp-amm position account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Position {
+    pub pool: Pubkey,
+    pub nft_mint: Pubkey,
+}
+
+impl Position {
+    /// Decode a position account, checking it is owned by cp-amm
+    pub fn try_from_account_info(position: &AccountInfo) -> Result<Self> {
+        if position.owner != &ID {
+            return Err(DammHonoraryFeeError::InvalidPositionOwner.into());
+        }
+
+        let data = position.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != POSITION_DISCRIMINATOR {
+            return Err(DammHonoraryFeeError::InvalidPositionOwner.into());
+        }
+        Self::deserialize(&mut &data[8..])
+            .map_err(|_| DammHonoraryFeeError::InvalidPositionOwner.into())
+    }
+}
+
+/// Accounts for cp-amm `create_position`, in instruction order
+pub struct CreatePosition<'info> {
+    pub owner: AccountInfo<'info>,
+    pub position_nft_mint: AccountInfo<'info>,
+    pub position_nft_account: AccountInfo<'info>,
+    pub pool: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub pool_authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Create a position in a pool, minting its NFT to `owner`
+pub fn create_position(accounts: CreatePosition) -> Result<()> {
+    let ix = Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.owner.key(), false),
+            AccountMeta::new(accounts.position_nft_mint.key(), true),
+            AccountMeta::new(accounts.position_nft_account.key(), false),
+            AccountMeta::new(accounts.pool.key(), false),
+            AccountMeta::new(accounts.position.key(), false),
+            AccountMeta::new_readonly(accounts.pool_authority.key(), false),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.event_authority.key(), false),
+            AccountMeta::new_readonly(accounts.program.key(), false),
+        ],
+        data: CREATE_POSITION_DISCRIMINATOR.to_vec(),
+    };
+
+    invoke(
+        &ix,
+        &[
+            accounts.owner,
+            accounts.position_nft_mint,
+            accounts.position_nft_account,
+            accounts.pool,
+            accounts.position,
+            accounts.pool_authority,
+            accounts.payer,
+            accounts.token_program,
+            accounts.system_program,
+            accounts.event_authority,
+            accounts.program,
+        ],
+    )
+    .map_err(Into::into)
+}
+
+/// Accounts for cp-amm `claim_position_fee`, in instruction order
+pub struct ClaimPositionFee<'info> {
+    pub pool_authority: AccountInfo<'info>,
+    pub pool: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub token_a_account: AccountInfo<'info>,
+    pub token_b_account: AccountInfo<'info>,
+    pub token_a_vault: AccountInfo<'info>,
+    pub token_b_vault: AccountInfo<'info>,
+    pub token_a_mint: AccountInfo<'info>,
+    pub token_b_mint: AccountInfo<'info>,
+    pub position_nft_account: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    pub token_a_program: AccountInfo<'info>,
+    pub token_b_program: AccountInfo<'info>,
+    pub event_authority: AccountInfo<'info>,
+    pub program: AccountInfo<'info>,
+}
+
+/// Claim all pending fees of a position to the owner's token accounts
+pub fn claim_position_fee<'info>(
+    accounts: ClaimPositionFee<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.pool_authority.key(), false),
+            AccountMeta::new_readonly(accounts.pool.key(), false),
+            AccountMeta::new(accounts.position.key(), false),
+            AccountMeta::new(accounts.token_a_account.key(), false),
+            AccountMeta::new(accounts.token_b_account.key(), false),
+            AccountMeta::new(accounts.token_a_vault.key(), false),
+            AccountMeta::new(accounts.token_b_vault.key(), false),
+            AccountMeta::new_readonly(accounts.token_a_mint.key(), false),
+            AccountMeta::new_readonly(accounts.token_b_mint.key(), false),
+            AccountMeta::new_readonly(accounts.position_nft_account.key(), false),
+            AccountMeta::new_readonly(accounts.owner.key(), true),
+            AccountMeta::new_readonly(accounts.token_a_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_b_program.key(), false),
+            AccountMeta::new_readonly(accounts.event_authority.key(), false),
+            AccountMeta::new_readonly(accounts.program.key(), false),
+        ],
+        data: CLAIM_POSITION_FEE_DISCRIMINATOR.to_vec(),
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            accounts.pool_authority,
+            accounts.pool,
+            accounts.position,
+            accounts.token_a_account,
+            accounts.token_b_account,
+            accounts.token_a_vault,
+            accounts.token_b_vault,
+            accounts.token_a_mint,
+            accounts.token_b_mint,
+            accounts.position_nft_account,
+            accounts.owner,
+            accounts.token_a_program,
+            accounts.token_b_program,
+            accounts.event_authority,
+            accounts.program,
+        ],
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
@@ -31,7 +31,7 @@ pub enum DammHonoraryFeeError {
     #[msg("Invalid pool token order - cannot determine quote mint")]
     InvalidPoolTokenOrder,
 
-    #[msg("Position tick range validation failed")]
+    #[msg("Pool price range validation failed")]
     InvalidTickRange,
 
     #[msg("Daily cap exceeded")]
@@ -42,4 +42,82 @@ pub enum DammHonoraryFeeError {
 
     #[msg("Unauthorized access to program function")]
     Unauthorized,
-}
\ No newline at end of file
+
+    #[msg("Investor quote ATA is missing or does not match the investor and quote mint")]
+    InvalidInvestorAta,
+
+    #[msg("Creator quote ATA does not belong to the policy creator or quote mint")]
+    InvalidCreatorAta,
+
+    #[msg("Payout pages do not match the day's locked snapshot")]
+    SnapshotMismatch,
+
+    #[msg("Dust ledger does not belong to this policy and investor stream")]
+    InvalidDustLedger,
+
+    #[msg("Pool account is not a cp-amm pool")]
+    InvalidPoolAccount,
+
+    #[msg("Honorary position or its NFT is not controlled by the owner PDA")]
+    InvalidPositionOwner,
+
+    #[msg("pool_id argument does not match the pool account")]
+    InvalidPoolId,
+
+    #[msg("vault_pubkey argument does not match the vault account")]
+    InvalidVaultPubkey,
+
+    #[msg("Investor fee share exceeds 10000 basis points")]
+    InvalidFeeShareBps,
+
+    #[msg("Quote mint account does not match the pool's quote mint")]
+    InvalidQuoteMint,
+
+    #[msg("Distribution is paused for this policy")]
+    DistributionPaused,
+
+    #[msg("Page index exceeds the maximum number of pages per day")]
+    TooManyPages,
+
+    #[msg("Epoch length must be positive and exceed the epoch offset")]
+    InvalidEpochLength,
+
+    #[msg("Page receipt account does not match the policy, day and page")]
+    InvalidPageReceipt,
+
+    #[msg("Page has already been paid today")]
+    PageAlreadyPaid,
+
+    #[msg("Page receipts can only be closed once their day has closed")]
+    DayNotClosed,
+
+    #[msg("Investor roster cannot change while a day is being distributed")]
+    RosterLocked,
+
+    #[msg("Investor roster page is full")]
+    RosterPageFull,
+
+    #[msg("Stream is already listed on this roster page")]
+    DuplicateRosterEntry,
+
+    #[msg("Stream is not listed on this roster page")]
+    RosterEntryNotFound,
+
+    #[msg("Base mint account does not match the pool's base mint")]
+    InvalidBaseMint,
+
+    #[msg("Pool token mint account is not an SPL token mint")]
+    InvalidPoolMint,
+
+    #[msg("Treasury token account is not the owner PDA's associated token account for the mint")]
+    InvalidTreasuryAta,
+
+    #[msg("Authority cannot be renounced while distribution is paused")]
+    RenounceWhilePaused,
+
+    #[msg("Roster entry marker is not the PDA of the policy and stream")]
+    InvalidRosterEntry,
+
+    #[msg("Roster page still lists investors")]
+    RosterPageNotEmpty,
+}
@@ -2,6 +2,8 @@
 
 use anchor_lang::prelude::*;
 
+use crate::state::{CatchUpMode, DistributionPhase, PolicyParams};
+
 /// Event emitted when an honorary position is initialized
 #[event]
 pub struct HonoraryPositionInitialized {
@@ -15,9 +17,9 @@ pub struct HonoraryPositionInitialized {
     pub owner_pda: Pubkey,
     /// The quote mint
     pub quote_mint: Pubkey,
-    /// The tick range
-    pub tick_lower: i32,
-    pub tick_upper: i32,
+    /// The price range (Q64.64) the position spans
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
 }
 
 /// Event emitted when quote fees are claimed
@@ -29,6 +31,38 @@ pub struct QuoteFeesClaimed {
     pub pool_id: Pubkey,
 }
 
+/// Event emitted for each page of the snapshot pass
+#[event]
+pub struct InvestorSnapshotPage {
+    /// The page index
+    pub page_index: u32,
+    /// Total locked amount recorded for this page
+    pub locked_total: u64,
+    /// Number of investors in this page
+    pub investor_count: u32,
+    /// Current day ID
+    pub day_id: u64,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
+}
+
+/// Event emitted when the snapshot pass completes and the investor pool is frozen
+#[event]
+pub struct DaySnapshotClosed {
+    /// The day ID
+    pub day_id: u64,
+    /// Quote fees claimed for this day
+    pub claimed_quote: u64,
+    /// Total locked amount across all pages
+    pub locked_total: u64,
+    /// Quote amount to be split among investors by locked share
+    pub investor_pool: u64,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
+    /// Number of epochs the day stands in for (the daily cap is scaled by it)
+    pub days_covered: u64,
+}
+
 /// Event emitted for each page of investor payouts
 #[event]
 pub struct InvestorPayoutPage {
@@ -40,6 +74,8 @@ pub struct InvestorPayoutPage {
     pub investor_count: u32,
     /// Current day ID
     pub day_id: u64,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
 }
 
 /// Event emitted when a day's distribution is closed
@@ -51,6 +87,14 @@ pub struct CreatorPayoutDayClosed {
     pub remainder_amount: u64,
     /// Total distributed to investors this day
     pub total_investor_payout: u64,
+    /// Investor share withheld by the daily cap and carried to the next day
+    pub capped_amount: u64,
+    /// Carry-over from the previous day included in this day's investor pool
+    pub carried_in: u64,
+    /// Dust and capped excess left in the treasury for the next day
+    pub carried_out: u64,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
 }
 
 /// Event emitted for individual investor payouts
@@ -64,4 +108,144 @@ pub struct InvestorPayout {
     pub locked_amount: u64,
     /// The page index this payout was part of
     pub page_index: u32,
-}
\ No newline at end of file
+}
+
+/// Event emitted when an investor's share accrues on their dust ledger
+#[event]
+pub struct InvestorDustAccrued {
+    /// The investor's Streamflow stream
+    pub stream_pubkey: Pubkey,
+    /// The share added to the ledger
+    pub amount: u64,
+    /// Total owed on the ledger after this accrual
+    pub owed_total: u64,
+}
+
+/// Event emitted when the policy authority schedules new policy parameters
+#[event]
+pub struct PolicyUpdated {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// Parameters in force until the update applies
+    pub old: PolicyParams,
+    /// Parameters applied from `effective_day_id`
+    pub new: PolicyParams,
+    /// First day the new parameters apply to
+    pub effective_day_id: u64,
+    /// Still-pending update this one replaces, which will never apply
+    pub replaced: Option<PolicyParams>,
+}
+
+/// Event emitted when the policy authority proposes a successor
+#[event]
+pub struct AuthorityProposed {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The current authority
+    pub authority: Pubkey,
+    /// The proposed authority
+    pub pending_authority: Pubkey,
+}
+
+/// Event emitted when the policy authority changes hands or is renounced
+#[event]
+pub struct AuthorityTransferred {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The previous authority
+    pub old_authority: Pubkey,
+    /// The new authority (`Pubkey::default()` if renounced)
+    pub new_authority: Pubkey,
+}
+
+/// Event emitted when the guardian is set, cleared or dropped on renounce
+#[event]
+pub struct GuardianUpdated {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The previous guardian
+    pub old_guardian: Option<Pubkey>,
+    /// The new guardian (`None` if cleared)
+    pub new_guardian: Option<Pubkey>,
+}
+
+/// Event emitted when the crank is paused or resumed
+#[event]
+pub struct PauseUpdated {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// Whether the crank is now paused
+    pub paused: bool,
+    /// The authority or guardian that signed
+    pub signer: Pubkey,
+}
+
+/// Event emitted when the crank finds epochs in which nobody cranked
+#[event]
+pub struct DaysSkipped {
+    /// The skipped day IDs, oldest first (at most `MAX_LISTED_SKIPPED_DAYS`)
+    pub skipped_day_ids: Vec<u64>,
+    /// Total number of skipped days
+    pub skipped_count: u64,
+    /// How the skipped days are distributed
+    pub catch_up_mode: CatchUpMode,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
+}
+
+/// Event emitted when a day left open past the day gate is folded into the next
+///
+/// Marks the `Open -> Open` abandon transition of `DayState`; a day
+/// interrupted by a pause is resumed instead and never emits it.
+#[event]
+pub struct DayAbandoned {
+    /// The abandoned day ID
+    pub day_id: u64,
+    /// The pass the day was in
+    pub phase: DistributionPhase,
+    /// Carry-in (snapshot pass) or unpaid investor pool (payout pass) added
+    /// to the carry-over
+    pub carried_out: u64,
+    /// Claimed fees returned to the unassigned fees (snapshot pass)
+    pub reassigned_claim: u64,
+    /// Length of the day (epoch) in seconds
+    pub epoch_seconds: u32,
+}
+
+/// Event emitted when the authority changes a page of the investor roster
+#[event]
+pub struct RosterPageUpdated {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The changed page
+    pub page_index: u32,
+    /// Stream added to or removed from the page (`None` for a new page)
+    pub stream_pubkey: Option<Pubkey>,
+    /// Whether the stream was added
+    pub added: bool,
+    /// Investors on the page after the change
+    pub entry_count: u32,
+}
+
+/// Event emitted when the authority removes the last page of the investor roster
+#[event]
+pub struct RosterPageRemoved {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The removed page
+    pub page_index: u32,
+}
+
+/// Event emitted when a removed investor's dust ledger is closed
+#[event]
+pub struct DustLedgerClosed {
+    /// The policy PDA
+    pub policy: Pubkey,
+    /// The removed investor's Streamflow stream
+    pub stream_pubkey: Pubkey,
+    /// Dust still owed on the ledger, released to the creator remainder
+    pub released_lamports: u64,
+}
+
+/// Maximum number of day IDs listed in a `DaysSkipped` event
+pub const MAX_LISTED_SKIPPED_DAYS: usize = 64;
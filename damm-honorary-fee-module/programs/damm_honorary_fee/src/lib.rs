@@ -3,19 +3,27 @@
 //! This program implements a DAMM v2 honorary fee position system with:
 //! - Quote-only fee position initialization
 //! - 24h permissionless distribution crank with pagination
-//! - Integration with cp-amm for fee claiming
-//! - Mock Streamflow integration for locked amount queries
+//! - Integration with cp-amm for fee claiming via CPI
+//! - On-chain Streamflow reads for locked amounts
 
 use anchor_lang::prelude::*;
-use std::collections::BTreeMap;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::{self, Token2022};
+use anchor_spl::token_interface;
 
 pub mod state;
 pub mod errors;
 pub mod events;
+pub mod cp_amm;
+pub mod streamflow;
+pub mod quote_mints;
 
 use state::*;
 use errors::*;
 use events::*;
+use cp_amm::CpAmm;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFp1J6");
 
@@ -24,39 +32,120 @@ pub mod damm_honorary_fee {
     use super::*;
 
     /// Initialize a new honorary fee position for a DAMM v2 pool
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_honorary_position(
         ctx: Context<InitializeHonoraryPosition>,
         pool_id: Pubkey,
-        tick_lower: i32,
-        tick_upper: i32,
         vault_pubkey: Pubkey,
         investor_fee_share_bps: u16,
         daily_cap_lamports: Option<u64>,
         min_payout_lamports: u64,
         y0_total_allocation: u64,
+        epoch_seconds: u32,
+        epoch_offset_seconds: u32,
+        catch_up_mode: CatchUpMode,
+        quote_mint_rule: QuoteMintRule,
     ) -> Result<()> {
-        let policy_pda = &mut ctx.accounts.policy_pda;
-        let honorary_position = &mut ctx.accounts.honorary_position;
+        PolicyParams {
+            creator_wallet: ctx.accounts.creator_wallet.key(),
+            investor_fee_share_bps,
+            daily_cap_lamports,
+            min_payout_lamports,
+            epoch_seconds,
+            epoch_offset_seconds,
+            catch_up_mode,
+        }
+        .validate()?;
 
         // Validate pool token order and identify quote mint
+        let pool_state = cp_amm::Pool::try_from_account_info(&ctx.accounts.pool)?;
         let quote_mint = identify_quote_mint(
-            &ctx.accounts.token_mint_0,
-            &ctx.accounts.token_mint_1,
-            pool_id,
+            &pool_state,
+            &ctx.accounts.token_mint_0.key(),
+            &ctx.accounts.token_mint_1.key(),
+            quote_mint_rule,
+        )?;
+        if ctx.accounts.quote_mint.key() != quote_mint {
+            return Err(DammHonoraryFeeError::InvalidQuoteMint.into());
+        }
+
+        // The base mint is whichever pool token is not the quote mint
+        let expected_base_mint = if quote_mint == ctx.accounts.token_mint_0.key() {
+            ctx.accounts.token_mint_1.key()
+        } else {
+            ctx.accounts.token_mint_0.key()
+        };
+        if ctx.accounts.base_mint.key() != expected_base_mint {
+            return Err(DammHonoraryFeeError::InvalidBaseMint.into());
+        }
+
+        // Validate the pool charges fees only in the quote token, and its
+        // price range for quote-only accrual against its price
+        let quote_is_token_a = quote_mint == pool_state.token_a_mint;
+        validate_quote_only_fee_mode(pool_state.collect_fee_mode, quote_is_token_a)?;
+        validate_quote_only_position(
+            pool_state.sqrt_min_price,
+            pool_state.sqrt_max_price,
+            pool_state.sqrt_price,
+            quote_is_token_a,
+        )?;
+
+        // Create the program treasury ATAs for the validated mints
+        ctx.accounts.create_treasury_ata(
+            ctx.accounts.program_quote_treasury_ata.to_account_info(),
+            ctx.accounts.quote_mint.to_account_info(),
+        )?;
+        ctx.accounts.create_treasury_ata(
+            ctx.accounts.program_base_treasury_ata.to_account_info(),
+            ctx.accounts.base_mint.to_account_info(),
+        )?;
+
+        // Create the honorary position, minting its NFT to the owner PDA
+        cp_amm::create_position(cp_amm::CreatePosition {
+            owner: ctx.accounts.investor_fee_position_owner_pda.to_account_info(),
+            position_nft_mint: ctx.accounts.position_nft_mint.to_account_info(),
+            position_nft_account: ctx.accounts.position_nft_account.to_account_info(),
+            pool: ctx.accounts.pool.to_account_info(),
+            position: ctx.accounts.position.to_account_info(),
+            pool_authority: ctx.accounts.pool_authority.to_account_info(),
+            payer: ctx.accounts.creator_wallet.to_account_info(),
+            token_program: ctx.accounts.token_2022_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            event_authority: ctx.accounts.event_authority.to_account_info(),
+            program: ctx.accounts.cp_amm_program.to_account_info(),
+        })?;
+        verify_position_ownership(
+            &ctx.accounts.position,
+            &ctx.accounts.position_nft_account,
+            &ctx.accounts.pool.key(),
+            &ctx.accounts.position_nft_mint.key(),
+            &ctx.accounts.investor_fee_position_owner_pda.key(),
         )?;
 
-        // Validate tick range for quote-only accrual
-        validate_quote_only_position(tick_lower, tick_upper)?;
+        let policy_pda = &mut ctx.accounts.policy_pda;
+        let honorary_position = &mut ctx.accounts.honorary_position;
 
         // Initialize policy PDA
         policy_pda.pool_id = pool_id;
         policy_pda.vault_pubkey = vault_pubkey;
         policy_pda.creator_wallet = ctx.accounts.creator_wallet.key();
         policy_pda.quote_mint = quote_mint;
+        policy_pda.base_mint = expected_base_mint;
         policy_pda.investor_fee_share_bps = investor_fee_share_bps;
         policy_pda.daily_cap_lamports = daily_cap_lamports;
         policy_pda.min_payout_lamports = min_payout_lamports;
         policy_pda.y0_total_allocation = y0_total_allocation;
+        policy_pda.epoch_seconds = epoch_seconds;
+        policy_pda.epoch_offset_seconds = epoch_offset_seconds;
+        policy_pda.catch_up_mode = catch_up_mode;
+        policy_pda.roster_page_count = 0;
+        policy_pda.authority = ctx.accounts.creator_wallet.key();
+        policy_pda.pending_authority = None;
+        policy_pda.guardian = None;
+        policy_pda.paused = false;
+        policy_pda.last_paused_ts = 0;
+        policy_pda.pending_params = None;
+        policy_pda.pending_effective_day_id = 0;
         policy_pda.bump = ctx.bumps.policy_pda;
 
         // Initialize honorary position account
@@ -65,8 +154,8 @@ pub mod damm_honorary_fee {
         honorary_position.position_nft_mint = ctx.accounts.position_nft_mint.key();
         honorary_position.owner_pda = ctx.accounts.investor_fee_position_owner_pda.key();
         honorary_position.quote_mint = quote_mint;
-        honorary_position.tick_lower = tick_lower;
-        honorary_position.tick_upper = tick_upper;
+        honorary_position.sqrt_min_price = pool_state.sqrt_min_price;
+        honorary_position.sqrt_max_price = pool_state.sqrt_max_price;
         honorary_position.bump = ctx.bumps.honorary_position;
 
         // Emit initialization event
@@ -76,134 +165,653 @@ pub mod damm_honorary_fee {
             position_nft_mint: ctx.accounts.position_nft_mint.key(),
             owner_pda: ctx.accounts.investor_fee_position_owner_pda.key(),
             quote_mint,
-            tick_lower,
-            tick_upper,
+            sqrt_min_price: pool_state.sqrt_min_price,
+            sqrt_max_price: pool_state.sqrt_max_price,
+        });
+
+        Ok(())
+    }
+
+    /// Initialize the progress PDA tracking daily distribution for a policy
+    pub fn initialize_progress(ctx: Context<InitializeProgress>) -> Result<()> {
+        let progress = &mut ctx.accounts.progress_pda;
+
+        progress.policy_id = ctx.accounts.policy_pda.key();
+        progress.day_id = 0;
+        progress.day_seq = 0;
+        progress.last_distribution_ts = 0;
+        progress.last_opened_ts = 0;
+        progress.cumulative_distributed_today = 0;
+        progress.carry_over_lamports = 0;
+        progress.cursor_idx = 0;
+        progress.day_state = DayState::Idle;
+        progress.paid_pages = [0; ProgressAccount::PAID_PAGES_BYTES];
+        progress.phase = DistributionPhase::Snapshot;
+        progress.claimed_quote_today = 0;
+        progress.days_covered_today = 0;
+        progress.unassigned_claim_lamports = 0;
+        progress.locked_total_today = 0;
+        progress.investor_pool_today = 0;
+        progress.locked_paid_today = 0;
+        progress.carried_in_today = 0;
+        progress.capped_today = 0;
+        progress.ledger_dust_lamports = 0;
+        progress.bump = ctx.bumps.progress_pda;
+
+        Ok(())
+    }
+
+    /// Create a dust ledger for an investor stream listed on the roster
+    ///
+    /// Payout-pass shares below `min_payout_lamports` accrue on the ledger
+    /// instead of the global carry-over, and are paid to the investor once
+    /// the owed total reaches the minimum payout. The ledger is closed when
+    /// the stream is removed from the roster.
+    pub fn initialize_dust_ledger(ctx: Context<InitializeDustLedger>) -> Result<()> {
+        let ledger = &mut ctx.accounts.dust_ledger;
+
+        ledger.policy_id = ctx.accounts.policy_pda.key();
+        ledger.stream_pubkey = ctx.accounts.stream.key();
+        ledger.owed_lamports = 0;
+        ledger.bump = ctx.bumps.dust_ledger;
+
+        Ok(())
+    }
+
+    /// Schedule new policy parameters, signed by the policy authority
+    ///
+    /// The parameters apply from the next epoch boundary, so a day already in
+    /// flight finishes under the parameters it was opened with. A later update
+    /// before that boundary replaces the pending one, and its event carries
+    /// the replaced parameters.
+    pub fn update_policy(ctx: Context<UpdatePolicy>, params: PolicyParams) -> Result<()> {
+        params.validate()?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let policy = &mut ctx.accounts.policy_pda;
+        policy.apply_pending_params(current_time);
+
+        let effective_day_id = policy.epoch_id(current_time).checked_add(1)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        let old = policy.params();
+        let replaced = policy.pending_params.replace(params);
+        policy.pending_effective_day_id = effective_day_id;
+
+        emit!(PolicyUpdated {
+            policy: policy.key(),
+            old,
+            new: params,
+            effective_day_id,
+            replaced,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new policy authority, which takes over once it accepts
+    pub fn propose_authority(ctx: Context<UpdatePolicy>, new_authority: Pubkey) -> Result<()> {
+        let policy = &mut ctx.accounts.policy_pda;
+        policy.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            policy: policy.key(),
+            authority: policy.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a proposed authority transfer, signed by the proposed key
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy_pda;
+        let old_authority = policy.authority;
+        policy.authority = ctx.accounts.pending_authority.key();
+        policy.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            policy: policy.key(),
+            old_authority,
+            new_authority: policy.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Give up the policy authority, making the policy immutable
+    ///
+    /// Any pending proposal is cancelled and the guardian is cleared, so
+    /// nobody can pause the crank afterwards. Renouncing while paused is
+    /// rejected, as nobody could resume it. Parameters already scheduled by
+    /// `update_policy` still apply at their effective day.
+    pub fn renounce_authority(ctx: Context<UpdatePolicy>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy_pda;
+        if policy.paused {
+            return Err(DammHonoraryFeeError::RenounceWhilePaused.into());
+        }
+
+        let old_authority = policy.authority;
+        policy.authority = Pubkey::default();
+        policy.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            policy: policy.key(),
+            old_authority,
+            new_authority: policy.authority,
+        });
+
+        if let Some(old_guardian) = policy.guardian.take() {
+            emit!(GuardianUpdated {
+                policy: policy.key(),
+                old_guardian: Some(old_guardian),
+                new_guardian: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Set or clear the guardian key, signed by the policy authority
+    pub fn set_guardian(ctx: Context<UpdatePolicy>, guardian: Option<Pubkey>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy_pda;
+        let old_guardian = policy.guardian;
+        policy.guardian = guardian;
+
+        emit!(GuardianUpdated {
+            policy: policy.key(),
+            old_guardian,
+            new_guardian: guardian,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume the crank, signed by the policy authority or guardian
+    ///
+    /// Progress is left untouched, so resuming mid-day continues from the
+    /// stored cursor. The pause time is recorded so that a day open when the
+    /// crank was paused is resumed rather than abandoned at the next day gate.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        let policy = &mut ctx.accounts.policy_pda;
+        policy.paused = paused;
+        if paused {
+            policy.last_paused_ts = Clock::get()?.unix_timestamp;
+        }
+
+        emit!(PauseUpdated {
+            policy: policy.key(),
+            paused,
+            signer: ctx.accounts.signer.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Append an empty page to the investor roster
+    pub fn initialize_roster_page(ctx: Context<InitializeRosterPage>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        check_roster_unlocked(&ctx.accounts.progress_pda, &ctx.accounts.policy_pda, current_time)?;
+
+        let policy = &mut ctx.accounts.policy_pda;
+        let page_index = policy.roster_page_count;
+        if page_index >= ProgressAccount::MAX_PAGES {
+            return Err(DammHonoraryFeeError::TooManyPages.into());
+        }
+        policy.roster_page_count = page_index + 1;
+
+        let roster_page = &mut ctx.accounts.roster_page;
+        roster_page.policy_id = policy.key();
+        roster_page.page_index = page_index;
+        roster_page.entries = Vec::new();
+        roster_page.bump = ctx.bumps.roster_page;
+        roster_page.snapshot_day_seq = 0;
+        roster_page.snapshot_locked = Vec::new();
+        roster_page.receipt_bump = 0;
+
+        emit!(RosterPageUpdated {
+            policy: policy.key(),
+            page_index,
+            stream_pubkey: None,
+            added: false,
+            entry_count: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Add an investor to a roster page
+    ///
+    /// Creates the stream's roster entry marker, so a stream listed on any
+    /// page of the roster cannot be added again.
+    pub fn add_roster_entry(ctx: Context<UpdateRoster>, page_index: u32, entry: InvestorAccount) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        check_roster_unlocked(&ctx.accounts.progress_pda, &ctx.accounts.policy_pda, current_time)?;
+
+        let policy_key = ctx.accounts.policy_pda.key();
+        let marker_bump = check_roster_entry(&ctx.accounts.roster_entry, &policy_key, &entry.stream_pubkey)?;
+        if ctx.accounts.roster_entry.owner == &crate::ID {
+            return Err(DammHonoraryFeeError::DuplicateRosterEntry.into());
+        }
+        if ctx.accounts.roster_page.entries.len() >= InvestorRoster::MAX_ENTRIES {
+            return Err(DammHonoraryFeeError::RosterPageFull.into());
+        }
+        ctx.accounts.create_roster_entry(&entry.stream_pubkey, page_index, marker_bump)?;
+
+        let stream_pubkey = entry.stream_pubkey;
+        let roster_page = &mut ctx.accounts.roster_page;
+        roster_page.entries.push(entry);
+
+        emit!(RosterPageUpdated {
+            policy: policy_key,
+            page_index,
+            stream_pubkey: Some(stream_pubkey),
+            added: true,
+            entry_count: roster_page.entries.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    /// Remove an investor from a roster page, closing its roster entry marker
+    ///
+    /// If the stream has a dust ledger it is closed too: the dust it still
+    /// owes is released to the creator remainder of the next day.
+    pub fn remove_roster_entry(ctx: Context<UpdateRoster>, page_index: u32, stream_pubkey: Pubkey) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        check_roster_unlocked(&ctx.accounts.progress_pda, &ctx.accounts.policy_pda, current_time)?;
+
+        let policy_key = ctx.accounts.policy_pda.key();
+        check_roster_entry(&ctx.accounts.roster_entry, &policy_key, &stream_pubkey)?;
+        let roster_page = &mut ctx.accounts.roster_page;
+        let position = roster_page.entries
+            .iter()
+            .position(|listed| listed.stream_pubkey == stream_pubkey)
+            .ok_or(DammHonoraryFeeError::RosterEntryNotFound)?;
+        roster_page.entries.remove(position);
+        let entry_count = roster_page.entries.len() as u32;
+        ctx.accounts.close_roster_entry()?;
+        ctx.accounts.close_dust_ledger(&stream_pubkey)?;
+
+        emit!(RosterPageUpdated {
+            policy: policy_key,
+            page_index,
+            stream_pubkey: Some(stream_pubkey),
+            added: false,
+            entry_count,
+        });
+
+        Ok(())
+    }
+
+    /// Remove the last, empty page of the investor roster, refunding its rent
+    ///
+    /// Entries are removed first with `remove_roster_entry`, so their markers
+    /// are closed with them.
+    pub fn remove_roster_page(ctx: Context<RemoveRosterPage>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        check_roster_unlocked(&ctx.accounts.progress_pda, &ctx.accounts.policy_pda, current_time)?;
+
+        let policy = &mut ctx.accounts.policy_pda;
+        let page_index = ctx.accounts.roster_page.page_index;
+        policy.roster_page_count = page_index;
+
+        emit!(RosterPageRemoved {
+            policy: policy.key(),
+            page_index,
         });
 
         Ok(())
     }
 
-    /// Crank to distribute fees for a page of investors
-    pub fn crank_distribute_page(
-        ctx: Context<CrankDistributePage>,
+    /// Crank to distribute fees for a page of the investor roster
+    ///
+    /// Each day runs in two passes over the roster pages. The snapshot pass
+    /// accumulates every investor's locked amount into the progress PDA; its
+    /// last page freezes the day's investor pool. The payout pass then pays
+    /// each investor `locked_i / locked_total_today` of that pool, and its
+    /// last page sends the remainder to the creator and closes the day.
+    pub fn crank_distribute_page<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankDistributePage<'info>>,
         page_index: u32,
-        is_final_page_in_day: bool,
-        investor_accounts: Vec<InvestorAccount>,
     ) -> Result<()> {
-        let policy = &ctx.accounts.policy_pda;
-        let progress = &mut ctx.accounts.progress_pda;
         let current_time = Clock::get()?.unix_timestamp;
+        let owner_bump = ctx.bumps.investor_fee_position_owner_pda;
 
-        // Validate day gate
-        let current_day_id = (current_time / 86400) as u64;
-        if current_day_id <= progress.day_id && current_time < progress.last_distribution_ts + 86400 {
-            return Err(DammHonoraryFeeError::DayGateNotOpen.into());
+        // Scheduled policy updates apply from their effective epoch onwards
+        let policy = &mut ctx.accounts.policy_pda;
+        policy.apply_pending_params(current_time);
+        let epoch_seconds = policy.epoch_seconds;
+        // The page's investors and the day's last page come from the roster
+        let is_final_page_in_day = page_index + 1 == policy.roster_page_count;
+        let investor_accounts = ctx.accounts.roster_page.entries.clone();
+        if ctx.accounts.creator_quote_ata.owner != ctx.accounts.policy_pda.creator_wallet {
+            return Err(DammHonoraryFeeError::InvalidCreatorAta.into());
         }
 
-        // Update day tracking if new day
-        if current_day_id > progress.day_id {
-            progress.day_id = current_day_id;
-            progress.last_distribution_ts = current_time;
+        // Open a new day once past the gate: claim once, freezing the claimed
+        // amount for the day. Pages of an open day proceed; a closed day
+        // rejects pages until the gate.
+        if ctx.accounts.progress_pda.opens_day(&ctx.accounts.policy_pda, current_time) {
+            if ctx.accounts.progress_pda.day_state == DayState::Open {
+                abandon_open_day(&mut ctx.accounts.progress_pda, epoch_seconds)?;
+            }
+            let claimed_quote = ctx.accounts.claim_quote_fees(owner_bump)?;
+            open_day(
+                &mut ctx.accounts.progress_pda,
+                &ctx.accounts.policy_pda,
+                current_time,
+                claimed_quote,
+            )?;
+
+            let progress = &mut ctx.accounts.progress_pda;
+            progress.day_seq = progress.day_seq.checked_add(1)
+                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
             progress.cumulative_distributed_today = 0;
+            // Yesterday's dust and capped excess roll into today's pool
+            progress.carried_in_today = progress.carry_over_lamports;
             progress.carry_over_lamports = 0;
-            progress.is_closed = false;
-            progress.page_payouts.clear();
+            progress.capped_today = 0;
+            progress.cursor_idx = 0;
+            progress.day_state = DayState::Open;
+            progress.paid_pages = [0; ProgressAccount::PAID_PAGES_BYTES];
+            progress.phase = DistributionPhase::Snapshot;
+            progress.locked_total_today = 0;
+            progress.investor_pool_today = 0;
+            progress.locked_paid_today = 0;
+        } else if ctx.accounts.progress_pda.day_state == DayState::Closed {
+            return Err(DammHonoraryFeeError::DayGateNotOpen.into());
         }
 
         // Validate pagination cursor
-        if page_index != progress.cursor_idx {
+        if page_index != ctx.accounts.progress_pda.cursor_idx {
             return Err(DammHonoraryFeeError::InvalidPaginationCursor.into());
         }
-
-        // Claim fees from position (mock implementation for now)
-        // TODO: Integrate with actual cp-amm claim_fees instruction
-        let claimed_quote = 0u64; // This would come from cp-amm claim
-
-        // Calculate total locked amount and validate no base fees
-        let mut total_locked: u64 = 0;
-        for investor in &investor_accounts {
-            total_locked = total_locked.checked_add(investor.locked_amount)
-                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        if page_index >= ProgressAccount::MAX_PAGES {
+            return Err(DammHonoraryFeeError::TooManyPages.into());
         }
 
-        // Calculate investor share
-        let y0 = policy.y0_total_allocation;
-        let locked_total = total_locked;
-        let f_locked = if y0 > 0 {
-            (locked_total as u128 * 10000 / y0 as u128) as u64
+        // The snapshot pass reads locked amounts as of the day's opening time
+        // and records them on the roster page; the payout pass pays from the
+        // recorded amounts, so stream changes in between cannot skew it. The
+        // snapshot also records the bump of the page's receipt PDA, sparing
+        // the payout pass the search for it.
+        let policy_key = ctx.accounts.policy_pda.key();
+        let day_seq = ctx.accounts.progress_pda.day_seq;
+        let locked_amounts = if ctx.accounts.progress_pda.phase == DistributionPhase::Snapshot {
+            let locked_amounts = read_page_locked_amounts(
+                ctx.remaining_accounts,
+                &investor_accounts,
+                &ctx.accounts.policy_pda.quote_mint,
+                &ctx.accounts.base_mint.key(),
+                ctx.accounts.progress_pda.last_distribution_ts,
+            )?;
+            let roster_page = &mut ctx.accounts.roster_page;
+            roster_page.snapshot_day_seq = day_seq;
+            roster_page.snapshot_locked = locked_amounts.clone();
+            roster_page.receipt_bump = find_page_receipt_bump(&policy_key, day_seq, page_index);
+            locked_amounts
         } else {
-            0
+            validate_page_quote_atas(
+                ctx.remaining_accounts,
+                &investor_accounts,
+                &ctx.accounts.policy_pda.quote_mint,
+            )?;
+            let roster_page = &ctx.accounts.roster_page;
+            if roster_page.snapshot_day_seq != day_seq
+                || roster_page.snapshot_locked.len() != investor_accounts.len()
+            {
+                return Err(DammHonoraryFeeError::SnapshotMismatch.into());
+            }
+            roster_page.snapshot_locked.clone()
         };
+        let mut page_locked: u64 = 0;
+        for locked_amount in &locked_amounts {
+            page_locked = page_locked.checked_add(*locked_amount)
+                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        }
 
-        let eligible_investor_share_bps = std::cmp::min(
-            policy.investor_fee_share_bps,
-            f_locked as u16,
-        );
+        let next_cursor = page_index.checked_add(1)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+
+        if ctx.accounts.progress_pda.phase == DistributionPhase::Snapshot {
+            let policy = &ctx.accounts.policy_pda;
+            let progress = &mut ctx.accounts.progress_pda;
+
+            progress.locked_total_today = progress.locked_total_today
+                .checked_add(page_locked)
+                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+            progress.cursor_idx = next_cursor;
+
+            emit!(InvestorSnapshotPage {
+                page_index,
+                locked_total: page_locked,
+                investor_count: investor_accounts.len() as u32,
+                day_id: progress.day_id,
+                epoch_seconds,
+            });
+
+            // Freeze the day's investor pool and rewind for the payout pass
+            if is_final_page_in_day {
+                let distributable = investor_pool_for_day(
+                    progress.claimed_quote_today,
+                    progress.locked_total_today,
+                    policy,
+                )
+                .checked_add(progress.carried_in_today)
+                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+
+                // Clamp to the daily cap, scaled by the epochs the day covers;
+                // the excess stays in the treasury for the next day. With
+                // nobody locked there is nobody to pay, and nobody left to
+                // carry for: the carry-in is released to the creator remainder.
+                let capped_pool = match policy.cap_for_days(progress.days_covered_today) {
+                    Some(cap) => std::cmp::min(distributable, cap),
+                    None => distributable,
+                };
+
+                if progress.locked_total_today == 0 {
+                    progress.investor_pool_today = 0;
+                    progress.capped_today = 0;
+                    progress.carry_over_lamports = 0;
+                } else {
+                    progress.investor_pool_today = capped_pool;
+                    progress.capped_today = distributable - capped_pool;
+                    progress.carry_over_lamports = distributable - capped_pool;
+                }
+                progress.phase = DistributionPhase::Payout;
+                progress.cursor_idx = 0;
+
+                emit!(DaySnapshotClosed {
+                    day_id: progress.day_id,
+                    claimed_quote: progress.claimed_quote_today,
+                    locked_total: progress.locked_total_today,
+                    investor_pool: progress.investor_pool_today,
+                    epoch_seconds,
+                    days_covered: progress.days_covered_today,
+                });
+            }
+
+            return Ok(());
+        }
 
-        let investor_fee_quote = ((claimed_quote as u128) * (eligible_investor_share_bps as u128) / 10000) as u64;
+        // The payout pass can only cover investors counted in the snapshot,
+        // and each page is paid at most once
+        let progress = &ctx.accounts.progress_pda;
+        if progress.is_page_paid(page_index) {
+            return Err(DammHonoraryFeeError::InvalidPaginationCursor.into());
+        }
+        let receipt_bump = ctx.accounts.roster_page.receipt_bump;
+        check_page_receipt(&ctx.accounts.page_receipt, &policy_key, day_seq, page_index, receipt_bump)?;
+        let locked_paid_today = progress.locked_paid_today
+            .checked_add(page_locked)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        if locked_paid_today > progress.locked_total_today
+            || (is_final_page_in_day && locked_paid_today != progress.locked_total_today)
+        {
+            return Err(DammHonoraryFeeError::SnapshotMismatch.into());
+        }
 
-        // Distribute to investors
+        // Compute every payout before moving funds
+        let policy_key = ctx.accounts.policy_pda.key();
+        let min_payout = ctx.accounts.policy_pda.min_payout_lamports;
+        let mut payouts = Vec::with_capacity(investor_accounts.len());
         let mut total_paid_this_page: u64 = 0;
-        for investor in investor_accounts {
-            let weight = if total_locked > 0 {
-                (investor.locked_amount as u128 * 10000 / total_locked as u128) as u64
+        let mut attributed_this_page: u64 = 0;
+        let mut dust_this_page: u64 = 0;
+        let mut ledger_accrued: u64 = 0;
+        let mut ledger_released: u64 = 0;
+        for ((investor, accounts), locked_amount) in investor_accounts
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(InvestorAccount::REMAINING_ACCOUNTS))
+            .zip(&locked_amounts)
+        {
+            let share = if progress.locked_total_today > 0 {
+                (progress.investor_pool_today as u128 * *locked_amount as u128
+                    / progress.locked_total_today as u128) as u64
             } else {
                 0
             };
 
-            let payout = (investor_fee_quote as u128 * weight as u128 / 10000) as u64;
+            let payout = match load_dust_ledger(&accounts[2], investor, &policy_key)? {
+                // Dust ledger: the share is owed to the investor either way
+                Some(mut ledger) => {
+                    attributed_this_page = attributed_this_page.checked_add(share)
+                        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                    let owed = ledger.owed_lamports.checked_add(share)
+                        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
 
-            if payout >= policy.min_payout_lamports {
-                // Transfer tokens to investor ATA
-                // TODO: Implement actual token transfer
+                    let payout = if owed >= min_payout {
+                        ledger_released = ledger_released.checked_add(ledger.owed_lamports)
+                            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                        ledger.owed_lamports = 0;
+                        Some(owed)
+                    } else {
+                        ledger_accrued = ledger_accrued.checked_add(share)
+                            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                        ledger.owed_lamports = owed;
 
+                        emit!(InvestorDustAccrued {
+                            stream_pubkey: investor.stream_pubkey,
+                            amount: share,
+                            owed_total: owed,
+                        });
+                        None
+                    };
+
+                    ledger.exit(&crate::ID)?;
+                    payout
+                }
+                None if share >= min_payout => {
+                    attributed_this_page = attributed_this_page.checked_add(share)
+                        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                    Some(share)
+                }
+                None => {
+                    // Carry over dust
+                    dust_this_page = dust_this_page.checked_add(share)
+                        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                    None
+                }
+            };
+
+            if let Some(payout) = payout {
                 total_paid_this_page = total_paid_this_page.checked_add(payout)
                     .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+            }
+            payouts.push(payout);
+        }
 
-                // Emit investor payout event
-                emit!(InvestorPayout {
-                    investor_quote_ata: investor.investor_quote_ata,
-                    amount: payout,
-                    locked_amount: investor.locked_amount,
-                    page_index,
-                });
-            } else {
-                // Carry over dust
-                progress.carry_over_lamports = progress.carry_over_lamports.checked_add(payout)
-                    .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        if total_paid_this_page > ctx.accounts.program_quote_treasury_ata.amount {
+            return Err(DammHonoraryFeeError::InsufficientTreasury.into());
+        }
+
+        // Payouts are bounded by the capped pool; guard against rounding drift
+        if let Some(cap) = ctx.accounts.policy_pda.cap_for_days(progress.days_covered_today) {
+            let distributed = progress.cumulative_distributed_today
+                .checked_add(attributed_this_page)
+                .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+            if distributed > cap {
+                return Err(DammHonoraryFeeError::DailyCapExceeded.into());
             }
         }
 
+        // Transfer tokens from the treasury to each investor ATA
+        for (((investor, accounts), locked_amount), payout) in investor_accounts
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(InvestorAccount::REMAINING_ACCOUNTS))
+            .zip(locked_amounts)
+            .zip(payouts)
+        {
+            let Some(payout) = payout else {
+                continue;
+            };
+
+            ctx.accounts.transfer_from_treasury(accounts[0].clone(), payout, owner_bump)?;
+
+            // Emit investor payout event
+            emit!(InvestorPayout {
+                investor_quote_ata: investor.investor_quote_ata,
+                amount: payout,
+                locked_amount,
+                page_index,
+            });
+        }
+
+        // Record the page as paid before updating progress tracking
+        let day_id = ctx.accounts.progress_pda.day_id;
+        ctx.accounts.create_page_receipt(day_id, day_seq, page_index, total_paid_this_page, receipt_bump)?;
+
         // Update progress tracking
-        progress.cumulative_distributed_today = progress.cumulative_distributed_today
-            .checked_add(total_paid_this_page)
+        let progress = &mut ctx.accounts.progress_pda;
+        progress.locked_paid_today = locked_paid_today;
+        progress.carry_over_lamports = progress.carry_over_lamports
+            .checked_add(dust_this_page)
             .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
-
-        progress.cursor_idx = page_index.checked_add(1)
+        progress.ledger_dust_lamports = progress.ledger_dust_lamports
+            .checked_add(ledger_accrued)
+            .and_then(|owed| owed.checked_sub(ledger_released))
             .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
-
-        progress.page_payouts.insert(page_index, total_paid_this_page);
+        progress.cumulative_distributed_today = progress.cumulative_distributed_today
+            .checked_add(attributed_this_page)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        progress.cursor_idx = next_cursor;
+        progress.mark_page_paid(page_index);
 
         // Handle final page of day
         if is_final_page_in_day {
-            let remainder = investor_fee_quote.checked_sub(total_paid_this_page)
+            let total_investor_payout = progress.cumulative_distributed_today;
+            let carried_in = progress.carried_in_today;
+            let carried_out = progress.carry_over_lamports;
+            let capped_amount = progress.capped_today;
+            let withheld = carried_out
+                .checked_add(progress.ledger_dust_lamports)
+                .and_then(|withheld| withheld.checked_add(progress.unassigned_claim_lamports))
                 .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
 
+            // Everything left in the treasury except the carry-over (dust and
+            // capped excess), dust owed through ledgers and fees held for days
+            // still to be replayed belongs to the creator
+            ctx.accounts.program_quote_treasury_ata.reload()?;
+            let remainder = ctx.accounts.program_quote_treasury_ata.amount
+                .saturating_sub(withheld);
+
             if remainder > 0 {
                 // Transfer remainder to creator
-                // TODO: Implement actual token transfer to creator
-                progress.cumulative_distributed_today = progress.cumulative_distributed_today
-                    .checked_add(remainder)
-                    .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+                let creator_quote_ata = ctx.accounts.creator_quote_ata.to_account_info();
+                ctx.accounts.transfer_from_treasury(creator_quote_ata, remainder, owner_bump)?;
             }
 
-            progress.is_closed = true;
+            ctx.accounts.progress_pda.day_state = DayState::Closed;
 
             // Emit day closed event
             emit!(CreatorPayoutDayClosed {
-                day_id: current_day_id,
+                day_id,
                 remainder_amount: remainder,
-                total_investor_payout: total_paid_this_page,
+                total_investor_payout,
+                capped_amount,
+                carried_in,
+                carried_out,
+                epoch_seconds,
             });
         }
 
@@ -212,27 +820,59 @@ pub mod damm_honorary_fee {
             page_index,
             paid_total: total_paid_this_page,
             investor_count: investor_accounts.len() as u32,
-            day_id: current_day_id,
+            day_id,
+            epoch_seconds,
         });
 
         Ok(())
     }
+
+    /// Close a page receipt once its day has closed, refunding its rent to
+    /// the cranker who paid it
+    pub fn close_page_receipt(ctx: Context<ClosePageReceipt>) -> Result<()> {
+        let progress = &ctx.accounts.progress_pda;
+        if ctx.accounts.page_receipt.day_seq == progress.day_seq && progress.day_state != DayState::Closed {
+            return Err(DammHonoraryFeeError::DayNotClosed.into());
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(pool_id: Pubkey, vault_key: Pubkey)]
 pub struct InitializeHonoraryPosition<'info> {
     /// The pool for which we're creating the honorary position
+    #[account(
+        mut,
+        owner = cp_amm::ID @ DammHonoraryFeeError::InvalidPoolAccount,
+        constraint = pool.key() == pool_id @ DammHonoraryFeeError::InvalidPoolId,
+    )]
     pub pool: AccountInfo<'info>,
 
     /// The token mints for the pool (for quote mint identification)
+    #[account(
+        owner = token::ID @ DammHonoraryFeeError::InvalidPoolMint,
+        constraint = is_spl_mint(&token_mint_0) @ DammHonoraryFeeError::InvalidPoolMint,
+    )]
     pub token_mint_0: AccountInfo<'info>,
+    #[account(
+        owner = token::ID @ DammHonoraryFeeError::InvalidPoolMint,
+        constraint = is_spl_mint(&token_mint_1) @ DammHonoraryFeeError::InvalidPoolMint,
+    )]
     pub token_mint_1: AccountInfo<'info>,
 
-    /// The position to be created (will be owned by program PDA)
+    /// The position to be created via cp-amm (controlled by the owner PDA)
+    #[account(mut)]
     pub position: AccountInfo<'info>,
 
-    /// The position NFT mint
-    pub position_nft_mint: AccountInfo<'info>,
+    /// The position NFT mint, a fresh keypair created by cp-amm
+    #[account(mut)]
+    pub position_nft_mint: Signer<'info>,
+
+    /// The owner PDA's token account receiving the position NFT
+    #[account(mut)]
+    pub position_nft_account: AccountInfo<'info>,
 
     /// The position NFT owner (program PDA)
     #[account(
@@ -242,16 +882,18 @@ pub struct InitializeHonoraryPosition<'info> {
     pub investor_fee_position_owner_pda: SystemAccount<'info>,
 
     /// The vault pubkey (used in PDA seeds)
+    #[account(constraint = vault_pubkey.key() == vault_key @ DammHonoraryFeeError::InvalidVaultPubkey)]
     pub vault_pubkey: AccountInfo<'info>,
 
     /// The creator wallet (for remainder distributions)
+    #[account(mut)]
     pub creator_wallet: Signer<'info>,
 
     /// The policy PDA storing configuration
     #[account(
         init,
         payer = creator_wallet,
-        space = 8 + std::mem::size_of::<PolicyAccount>(),
+        space = PolicyAccount::LEN,
         seeds = [b"policy", pool.key().as_ref()],
         bump,
     )]
@@ -261,99 +903,1295 @@ pub struct InitializeHonoraryPosition<'info> {
     #[account(
         init,
         payer = creator_wallet,
-        space = 8 + std::mem::size_of::<HonoraryPositionAccount>(),
+        space = HonoraryPositionAccount::LEN,
         seeds = [b"honorary_position", pool.key().as_ref()],
         bump,
     )]
     pub honorary_position: Account<'info, HonoraryPositionAccount>,
 
-    /// The program quote treasury ATA
+    /// The quote mint (identified from pool tokens)
     #[account(
-        init,
-        payer = creator_wallet,
-        associated_token::mint = quote_mint,
-        associated_token::authority = investor_fee_position_owner_pda,
+        owner = token::ID @ DammHonoraryFeeError::InvalidQuoteMint,
+        constraint = is_spl_mint(&quote_mint) @ DammHonoraryFeeError::InvalidQuoteMint,
     )]
-    pub program_quote_treasury_ata: Account<'info, TokenAccount>,
-
-    /// The quote mint (identified from pool tokens)
     pub quote_mint: AccountInfo<'info>,
 
+    /// The base mint (the pool token that is not the quote mint)
+    #[account(
+        owner = token::ID @ DammHonoraryFeeError::InvalidBaseMint,
+        constraint = is_spl_mint(&base_mint) @ DammHonoraryFeeError::InvalidBaseMint,
+    )]
+    pub base_mint: AccountInfo<'info>,
+
+    /// The program quote treasury ATA, created once the mints are validated
+    #[account(
+        mut,
+        address = associated_token::get_associated_token_address(
+            &investor_fee_position_owner_pda.key(),
+            &quote_mint.key(),
+        ) @ DammHonoraryFeeError::InvalidTreasuryAta,
+    )]
+    pub program_quote_treasury_ata: AccountInfo<'info>,
+
+    /// The program base treasury ATA (must never receive funds)
+    #[account(
+        mut,
+        address = associated_token::get_associated_token_address(
+            &investor_fee_position_owner_pda.key(),
+            &base_mint.key(),
+        ) @ DammHonoraryFeeError::InvalidTreasuryAta,
+    )]
+    pub program_base_treasury_ata: AccountInfo<'info>,
+
     /// Token program
     pub token_program: Program<'info, Token>,
 
+    /// Token-2022 program, which cp-amm mints position NFTs with
+    pub token_2022_program: Program<'info, Token2022>,
+
     /// Associated token program
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     /// System program
     pub system_program: Program<'info, System>,
+
+    /// cp-amm pool authority
+    pub pool_authority: AccountInfo<'info>,
+
+    /// cp-amm event authority
+    pub event_authority: AccountInfo<'info>,
+
+    /// cp-amm program
+    pub cp_amm_program: Program<'info, CpAmm>,
+}
+
+impl<'info> InitializeHonoraryPosition<'info> {
+    /// Create the owner PDA's associated token account for a validated mint
+    fn create_treasury_ata(&self, ata: AccountInfo<'info>, mint: AccountInfo<'info>) -> Result<()> {
+        associated_token::create(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: self.creator_wallet.to_account_info(),
+                associated_token: ata,
+                authority: self.investor_fee_position_owner_pda.to_account_info(),
+                mint,
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))
+    }
 }
 
 #[derive(Accounts)]
-pub struct CrankDistributePage<'info> {
-    /// The policy PDA
+pub struct InitializeProgress<'info> {
+    /// The policy PDA whose distribution progress is tracked
     pub policy_pda: Account<'info, PolicyAccount>,
 
-    /// The honorary position account
-    pub honorary_position: Account<'info, HonoraryPositionAccount>,
-
     /// The progress PDA tracking distribution state
     #[account(
+        init,
+        payer = payer,
+        space = ProgressAccount::LEN,
         seeds = [b"progress", policy_pda.key().as_ref()],
         bump,
     )]
     pub progress_pda: Account<'info, ProgressAccount>,
 
-    /// The program quote treasury ATA (source of funds)
+    /// Pays rent for the progress PDA
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDustLedger<'info> {
+    /// The policy PDA the ledger belongs to
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The investor's Streamflow stream
+    #[account(owner = streamflow::ID @ DammHonoraryFeeError::StreamflowReadError)]
+    pub stream: AccountInfo<'info>,
+
+    /// The stream's roster entry marker: only listed streams get a ledger
     #[account(
-        associated_token::mint = quote_mint,
-        associated_token::authority = investor_fee_position_owner_pda,
+        owner = crate::ID @ DammHonoraryFeeError::RosterEntryNotFound,
+        seeds = [b"roster_entry", policy_pda.key().as_ref(), stream.key().as_ref()],
+        bump,
     )]
-    pub program_quote_treasury_ata: Account<'info, TokenAccount>,
+    pub roster_entry: AccountInfo<'info>,
 
-    /// The position owner PDA (for claiming fees)
+    /// The dust ledger for this investor
     #[account(
-        seeds = [b"vault", vault_pubkey.key().as_ref(), b"investor_fee_pos_owner"],
+        init,
+        payer = payer,
+        space = InvestorDustLedger::LEN,
+        seeds = [b"dust_ledger", policy_pda.key().as_ref(), stream.key().as_ref()],
         bump,
     )]
-    pub investor_fee_position_owner_pda: SystemAccount<'info>,
+    pub dust_ledger: Account<'info, InvestorDustLedger>,
 
-    /// The vault pubkey (from policy)
-    pub vault_pubkey: AccountInfo<'info>,
+    /// Pays rent for the dust ledger
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
-    /// The quote mint
-    pub quote_mint: AccountInfo<'info>,
+    /// System program
+    pub system_program: Program<'info, System>,
+}
 
-    /// Token program
-    pub token_program: Program<'info, Token>,
+/// Accounts for the instructions signed by the policy authority
+#[derive(Accounts)]
+pub struct UpdatePolicy<'info> {
+    /// The policy PDA to update
+    #[account(mut, has_one = authority @ DammHonoraryFeeError::Unauthorized)]
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The policy authority
+    pub authority: Signer<'info>,
 }
 
-/// Helper function to identify quote mint from pool tokens
-fn identify_quote_mint(
-    token_mint_0: &AccountInfo,
-    token_mint_1: &AccountInfo,
-    pool_id: Pubkey,
-) -> Result<Pubkey> {
-    // This is a simplified implementation
-    // In practice, this would involve querying the pool state or using
-    // a deterministic method to identify which token is the quote token
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// The policy PDA whose authority is transferred
+    #[account(
+        mut,
+        constraint = policy_pda.pending_authority == Some(pending_authority.key())
+            @ DammHonoraryFeeError::Unauthorized,
+    )]
+    pub policy_pda: Account<'info, PolicyAccount>,
 
-    // For now, assume token_mint_0 is quote mint (first in pair)
-    // TODO: Implement proper quote mint identification based on pool configuration
-    Ok(token_mint_0.key())
+    /// The proposed authority, proving control of its key
+    pub pending_authority: Signer<'info>,
 }
 
-/// Helper function to validate tick range for quote-only accrual
-fn validate_quote_only_position(tick_lower: i32, tick_upper: i32) -> Result<()> {
-    // This is a simplified validation
-    // In practice, this would involve complex logic to determine if a tick range
-    // can only accrue quote fees based on the pool's price range and token ordering
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// The policy PDA whose crank is paused or resumed
+    #[account(
+        mut,
+        constraint = signer.key() == policy_pda.authority
+            || policy_pda.guardian == Some(signer.key())
+            @ DammHonoraryFeeError::Unauthorized,
+    )]
+    pub policy_pda: Account<'info, PolicyAccount>,
 
-    // For demonstration, we'll accept any range for now
-    // TODO: Implement proper quote-only validation logic
-    if tick_lower >= tick_upper {
-        return Err(DammHonoraryFeeError::InvalidTickRange.into());
-    }
+    /// The policy authority or guardian
+    pub signer: Signer<'info>,
+}
 
-    Ok(())
+#[derive(Accounts)]
+pub struct InitializeRosterPage<'info> {
+    /// The policy PDA whose roster grows
+    #[account(mut, has_one = authority @ DammHonoraryFeeError::Unauthorized)]
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The progress PDA, telling whether a day is being distributed
+    #[account(
+        seeds = [b"progress", policy_pda.key().as_ref()],
+        bump = progress_pda.bump,
+    )]
+    pub progress_pda: Account<'info, ProgressAccount>,
+
+    /// The new roster page, at the end of the roster
+    #[account(
+        init,
+        payer = authority,
+        space = InvestorRoster::LEN,
+        seeds = [
+            b"roster",
+            policy_pda.key().as_ref(),
+            policy_pda.roster_page_count.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub roster_page: Account<'info, InvestorRoster>,
+
+    /// The policy authority, paying rent for the page
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct UpdateRoster<'info> {
+    /// The policy PDA whose roster changes
+    #[account(has_one = authority @ DammHonoraryFeeError::Unauthorized)]
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The progress PDA, telling whether a day is being distributed and
+    /// holding the dust owed through dust ledgers
+    #[account(
+        mut,
+        seeds = [b"progress", policy_pda.key().as_ref()],
+        bump = progress_pda.bump,
+    )]
+    pub progress_pda: Account<'info, ProgressAccount>,
+
+    /// The roster page to edit
+    #[account(
+        mut,
+        seeds = [b"roster", policy_pda.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = roster_page.bump,
+    )]
+    pub roster_page: Account<'info, InvestorRoster>,
+
+    /// The stream's `RosterEntry` marker PDA, created on add and closed on remove
+    #[account(mut)]
+    pub roster_entry: AccountInfo<'info>,
+
+    /// The stream's dust ledger PDA, closed on remove if it exists
+    #[account(mut)]
+    pub dust_ledger: AccountInfo<'info>,
+
+    /// The policy authority, paying and receiving the marker's and ledger's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateRoster<'info> {
+    /// Create the stream's roster entry marker, funded by the authority
+    fn create_roster_entry(&self, stream_pubkey: &Pubkey, page_index: u32, bump: u8) -> Result<()> {
+        let policy_key = self.policy_pda.key();
+        let [seed, policy, stream] = RosterEntry::seeds(&policy_key, stream_pubkey);
+
+        create_program_account(
+            &self.roster_entry,
+            self.authority.to_account_info(),
+            self.system_program.to_account_info(),
+            &[seed, policy, stream, &[bump]],
+            RosterEntry::LEN,
+        )?;
+
+        let roster_entry = RosterEntry {
+            policy_id: policy_key,
+            stream_pubkey: *stream_pubkey,
+            page_index,
+            bump,
+        };
+        let mut data = self.roster_entry.try_borrow_mut_data()?;
+        roster_entry.try_serialize(&mut &mut data[..])
+    }
+
+    /// Close the stream's roster entry marker, refunding its rent to the authority
+    fn close_roster_entry(&self) -> Result<()> {
+        if self.roster_entry.owner != &crate::ID {
+            return Err(DammHonoraryFeeError::RosterEntryNotFound.into());
+        }
+
+        close_program_account(&self.roster_entry, &self.authority.to_account_info())
+    }
+
+    /// Close the stream's dust ledger if it exists, refunding its rent to the
+    /// authority and releasing the dust it owes
+    fn close_dust_ledger(&mut self, stream_pubkey: &Pubkey) -> Result<()> {
+        let policy_key = self.policy_pda.key();
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"dust_ledger", policy_key.as_ref(), stream_pubkey.as_ref()],
+            &crate::ID,
+        );
+        if self.dust_ledger.key() != expected {
+            return Err(DammHonoraryFeeError::InvalidDustLedger.into());
+        }
+        if self.dust_ledger.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let owed_lamports = {
+            let data = self.dust_ledger.try_borrow_data()?;
+            InvestorDustLedger::try_deserialize(&mut &data[..])?.owed_lamports
+        };
+        let progress = &mut self.progress_pda;
+        progress.ledger_dust_lamports = progress.ledger_dust_lamports
+            .checked_sub(owed_lamports)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+        close_program_account(&self.dust_ledger, &self.authority.to_account_info())?;
+
+        emit!(DustLedgerClosed {
+            policy: policy_key,
+            stream_pubkey: *stream_pubkey,
+            released_lamports: owed_lamports,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveRosterPage<'info> {
+    /// The policy PDA whose roster shrinks
+    #[account(mut, has_one = authority @ DammHonoraryFeeError::Unauthorized)]
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The progress PDA, telling whether a day is being distributed
+    #[account(
+        seeds = [b"progress", policy_pda.key().as_ref()],
+        bump = progress_pda.bump,
+    )]
+    pub progress_pda: Account<'info, ProgressAccount>,
+
+    /// The last roster page, which must be empty
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"roster",
+            policy_pda.key().as_ref(),
+            policy_pda.roster_page_count.saturating_sub(1).to_le_bytes().as_ref(),
+        ],
+        bump = roster_page.bump,
+        constraint = roster_page.entries.is_empty() @ DammHonoraryFeeError::RosterPageNotEmpty,
+    )]
+    pub roster_page: Account<'info, InvestorRoster>,
+
+    /// The policy authority, receiving the page's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct CrankDistributePage<'info> {
+    /// The policy PDA
+    #[account(
+        mut,
+        seeds = [b"policy", pool.key().as_ref()],
+        bump = policy_pda.bump,
+        has_one = vault_pubkey @ DammHonoraryFeeError::InvalidVaultPubkey,
+        has_one = quote_mint @ DammHonoraryFeeError::InvalidQuoteMint,
+        has_one = base_mint @ DammHonoraryFeeError::InvalidBaseMint,
+        constraint = policy_pda.pool_id == pool.key() @ DammHonoraryFeeError::InvalidPoolId,
+        constraint = !policy_pda.paused @ DammHonoraryFeeError::DistributionPaused,
+    )]
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The honorary position account
+    #[account(
+        seeds = [b"honorary_position", pool.key().as_ref()],
+        bump = honorary_position.bump,
+    )]
+    pub honorary_position: Account<'info, HonoraryPositionAccount>,
+
+    /// The progress PDA tracking distribution state
+    #[account(
+        mut,
+        seeds = [b"progress", policy_pda.key().as_ref()],
+        bump = progress_pda.bump,
+        constraint = progress_pda.policy_id == policy_pda.key(),
+    )]
+    pub progress_pda: Account<'info, ProgressAccount>,
+
+    /// The roster page listing the page's investors
+    #[account(
+        mut,
+        seeds = [b"roster", policy_pda.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = roster_page.bump,
+    )]
+    pub roster_page: Account<'info, InvestorRoster>,
+
+    /// The program quote treasury ATA (source of funds)
+    #[account(
+        mut,
+        address = associated_token::get_associated_token_address(
+            &investor_fee_position_owner_pda.key(),
+            &policy_pda.quote_mint,
+        ) @ DammHonoraryFeeError::InvalidTreasuryAta,
+    )]
+    pub program_quote_treasury_ata: Account<'info, TokenAccount>,
+
+    /// The program base treasury ATA (receives the base side of the claim)
+    #[account(
+        mut,
+        address = associated_token::get_associated_token_address(
+            &investor_fee_position_owner_pda.key(),
+            &policy_pda.base_mint,
+        ) @ DammHonoraryFeeError::InvalidTreasuryAta,
+    )]
+    pub program_base_treasury_ata: Account<'info, TokenAccount>,
+
+    /// The creator's quote token account (receives the day's remainder);
+    /// its owner is checked once pending policy updates are applied
+    #[account(
+        mut,
+        constraint = creator_quote_ata.mint == policy_pda.quote_mint @ DammHonoraryFeeError::InvalidCreatorAta,
+    )]
+    pub creator_quote_ata: Account<'info, TokenAccount>,
+
+    /// The position owner PDA (for claiming fees)
+    #[account(
+        seeds = [b"vault", vault_pubkey.key().as_ref(), b"investor_fee_pos_owner"],
+        bump,
+    )]
+    pub investor_fee_position_owner_pda: SystemAccount<'info>,
+
+    /// The vault pubkey (from policy)
+    pub vault_pubkey: AccountInfo<'info>,
+
+    /// The quote mint (from policy)
+    pub quote_mint: AccountInfo<'info>,
+
+    /// The base mint (from policy)
+    pub base_mint: AccountInfo<'info>,
+
+    /// The cp-amm pool the honorary position belongs to
+    pub pool: AccountInfo<'info>,
+
+    /// The cp-amm pool authority (owns the pool vaults)
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The honorary cp-amm position
+    #[account(mut, address = honorary_position.position_id)]
+    pub position: AccountInfo<'info>,
+
+    /// The owner PDA's token account holding the position NFT
+    #[account(
+        constraint = position_nft_account.mint == honorary_position.position_nft_mint
+            @ DammHonoraryFeeError::InvalidPositionOwner,
+        constraint = position_nft_account.owner == investor_fee_position_owner_pda.key()
+            @ DammHonoraryFeeError::InvalidPositionOwner,
+    )]
+    pub position_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// The pool's token A vault
+    #[account(mut)]
+    pub token_a_vault: AccountInfo<'info>,
+
+    /// The pool's token B vault
+    #[account(mut)]
+    pub token_b_vault: AccountInfo<'info>,
+
+    /// The pool's token A mint
+    pub token_a_mint: AccountInfo<'info>,
+
+    /// The pool's token B mint
+    pub token_b_mint: AccountInfo<'info>,
+
+    /// The cp-amm event authority
+    pub event_authority: AccountInfo<'info>,
+
+    /// The cp-amm program
+    pub cp_amm_program: Program<'info, CpAmm>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// Receipt PDA of this page for the day being paid, created by the payout pass
+    #[account(mut)]
+    pub page_receipt: AccountInfo<'info>,
+
+    /// The cranker, paying rent for the page receipt
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CrankDistributePage<'info> {
+    /// Claim the honorary position's fees via cp-amm, returning the quote
+    /// amount measured as the treasury balance delta
+    fn claim_quote_fees(&mut self, owner_bump: u8) -> Result<u64> {
+        let token_a_mint = self.token_a_mint.key();
+        let token_b_mint = self.token_b_mint.key();
+        let base_mint = self.base_mint.key();
+        let quote_is_token_a = if token_a_mint == self.policy_pda.quote_mint && token_b_mint == base_mint {
+            true
+        } else if token_b_mint == self.policy_pda.quote_mint && token_a_mint == base_mint {
+            false
+        } else {
+            return Err(DammHonoraryFeeError::InvalidPoolTokenOrder.into());
+        };
+
+        let quote_before = self.program_quote_treasury_ata.amount;
+        let base_before = self.program_base_treasury_ata.amount;
+
+        let quote_treasury = self.program_quote_treasury_ata.to_account_info();
+        let base_treasury = self.program_base_treasury_ata.to_account_info();
+        let (token_a_account, token_b_account) = if quote_is_token_a {
+            (quote_treasury, base_treasury)
+        } else {
+            (base_treasury, quote_treasury)
+        };
+
+        let vault_key = self.vault_pubkey.key();
+        let owner_seeds: &[&[u8]] = &[
+            b"vault",
+            vault_key.as_ref(),
+            b"investor_fee_pos_owner",
+            &[owner_bump],
+        ];
+
+        cp_amm::claim_position_fee(
+            cp_amm::ClaimPositionFee {
+                pool_authority: self.pool_authority.to_account_info(),
+                pool: self.pool.to_account_info(),
+                position: self.position.to_account_info(),
+                token_a_account,
+                token_b_account,
+                token_a_vault: self.token_a_vault.to_account_info(),
+                token_b_vault: self.token_b_vault.to_account_info(),
+                token_a_mint: self.token_a_mint.to_account_info(),
+                token_b_mint: self.token_b_mint.to_account_info(),
+                position_nft_account: self.position_nft_account.to_account_info(),
+                owner: self.investor_fee_position_owner_pda.to_account_info(),
+                token_a_program: self.token_program.to_account_info(),
+                token_b_program: self.token_program.to_account_info(),
+                event_authority: self.event_authority.to_account_info(),
+                program: self.cp_amm_program.to_account_info(),
+            },
+            &[owner_seeds],
+        )?;
+
+        // Any base token arriving means the position is not quote-only;
+        // failing here reverts the claim and the whole page atomically
+        self.program_base_treasury_ata.reload()?;
+        if self.program_base_treasury_ata.amount != base_before {
+            return Err(DammHonoraryFeeError::BaseFeesObserved.into());
+        }
+
+        self.program_quote_treasury_ata.reload()?;
+        let claimed_quote = self.program_quote_treasury_ata.amount
+            .checked_sub(quote_before)
+            .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+
+        emit!(QuoteFeesClaimed {
+            amount: claimed_quote,
+            pool_id: self.policy_pda.pool_id,
+        });
+
+        Ok(claimed_quote)
+    }
+
+    /// Transfer quote tokens out of the program treasury, signed by the owner PDA
+    fn transfer_from_treasury(&self, to: AccountInfo<'info>, amount: u64, owner_bump: u8) -> Result<()> {
+        let vault_key = self.vault_pubkey.key();
+        let owner_seeds: &[&[u8]] = &[
+            b"vault",
+            vault_key.as_ref(),
+            b"investor_fee_pos_owner",
+            &[owner_bump],
+        ];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.program_quote_treasury_ata.to_account_info(),
+                    to,
+                    authority: self.investor_fee_position_owner_pda.to_account_info(),
+                },
+                &[owner_seeds],
+            ),
+            amount,
+        )
+    }
+
+    /// Create the page's receipt PDA, funded by the cranker
+    fn create_page_receipt(
+        &self,
+        day_id: u64,
+        day_seq: u64,
+        page_index: u32,
+        paid_lamports: u64,
+        bump: u8,
+    ) -> Result<()> {
+        let policy_key = self.policy_pda.key();
+        let day_bytes = day_seq.to_le_bytes();
+        let page_bytes = page_index.to_le_bytes();
+        let [seed, policy, day, page] = PageReceipt::seeds(&policy_key, &day_bytes, &page_bytes);
+
+        let receipt = self.page_receipt.to_account_info();
+        create_program_account(
+            &receipt,
+            self.payer.to_account_info(),
+            self.system_program.to_account_info(),
+            &[seed, policy, day, page, &[bump]],
+            PageReceipt::LEN,
+        )?;
+
+        let page_receipt = PageReceipt {
+            policy_id: policy_key,
+            day_id,
+            day_seq,
+            page_index,
+            payer: self.payer.key(),
+            paid_lamports,
+            bump,
+        };
+        let mut data = receipt.try_borrow_mut_data()?;
+        page_receipt.try_serialize(&mut &mut data[..])
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePageReceipt<'info> {
+    /// The policy PDA the receipt belongs to
+    pub policy_pda: Account<'info, PolicyAccount>,
+
+    /// The progress PDA, telling whether the receipt's day has closed
+    #[account(
+        seeds = [b"progress", policy_pda.key().as_ref()],
+        bump = progress_pda.bump,
+    )]
+    pub progress_pda: Account<'info, ProgressAccount>,
+
+    /// The receipt to close
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer,
+        seeds = [
+            b"page_receipt",
+            policy_pda.key().as_ref(),
+            page_receipt.day_seq.to_le_bytes().as_ref(),
+            page_receipt.page_index.to_le_bytes().as_ref(),
+        ],
+        bump = page_receipt.bump,
+    )]
+    pub page_receipt: Account<'info, PageReceipt>,
+
+    /// The cranker who paid the receipt's rent
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+}
+
+/// Reject roster changes while a day is being distributed
+///
+/// A day left open past the day gate is abandoned by the next crank, so the
+/// roster may change then; otherwise both passes must see the same pages. The
+/// gate is evaluated as the crank would, with any due policy update applied.
+fn check_roster_unlocked(progress: &ProgressAccount, policy: &PolicyAccount, current_time: i64) -> Result<()> {
+    let mut policy = policy.clone();
+    policy.apply_pending_params(current_time);
+    if progress.day_state == DayState::Open && !progress.opens_day(&policy, current_time) {
+        return Err(DammHonoraryFeeError::RosterLocked.into());
+    }
+
+    Ok(())
+}
+
+/// Find the bump of the page's receipt PDA for the day
+fn find_page_receipt_bump(policy_key: &Pubkey, day_seq: u64, page_index: u32) -> u8 {
+    let day_bytes = day_seq.to_le_bytes();
+    let page_bytes = page_index.to_le_bytes();
+    Pubkey::find_program_address(&PageReceipt::seeds(policy_key, &day_bytes, &page_bytes), &crate::ID).1
+}
+
+/// Check that `receipt` is the receipt PDA of the page for the day, derived
+/// with the bump recorded by the snapshot pass, and that the page has not
+/// been paid
+fn check_page_receipt(receipt: &AccountInfo, policy_key: &Pubkey, day_seq: u64, page_index: u32, bump: u8) -> Result<()> {
+    let day_bytes = day_seq.to_le_bytes();
+    let page_bytes = page_index.to_le_bytes();
+    let [seed, policy, day, page] = PageReceipt::seeds(policy_key, &day_bytes, &page_bytes);
+    let expected = Pubkey::create_program_address(&[seed, policy, day, page, &[bump]], &crate::ID)
+        .map_err(|_| DammHonoraryFeeError::InvalidPageReceipt)?;
+    if receipt.key() != expected {
+        return Err(DammHonoraryFeeError::InvalidPageReceipt.into());
+    }
+    if receipt.owner == &crate::ID {
+        return Err(DammHonoraryFeeError::PageAlreadyPaid.into());
+    }
+
+    Ok(())
+}
+
+/// Create a PDA owned by this program, funded by `payer`
+///
+/// Lamports sent to the address beforehand do not block creation: the
+/// account is topped up to rent exemption, allocated and assigned.
+fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                system_program::CreateAccount { from: payer, to: account.clone() },
+                &[seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer { from: payer, to: account.clone() },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate { account_to_allocate: account.clone() },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            system_program::Assign { account_to_assign: account.clone() },
+            &[seeds],
+        ),
+        &crate::ID,
+    )
+}
+
+/// Check that `marker` is the roster entry PDA of the stream, returning its bump
+fn check_roster_entry(marker: &AccountInfo, policy_key: &Pubkey, stream_pubkey: &Pubkey) -> Result<u8> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &RosterEntry::seeds(policy_key, stream_pubkey),
+        &crate::ID,
+    );
+    if marker.key() != expected {
+        return Err(DammHonoraryFeeError::InvalidRosterEntry.into());
+    }
+
+    Ok(bump)
+}
+
+/// Helper function to close a program-owned account, moving its lamports to
+/// `destination` and handing it back to the system program
+fn close_program_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination.lamports()
+        .checked_add(lamports)
+        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+/// Helper function to check an account holds an initialized SPL Token mint
+fn is_spl_mint(account: &AccountInfo) -> bool {
+    let Ok(data) = account.try_borrow_data() else {
+        return false;
+    };
+    Mint::try_deserialize(&mut &data[..]).is_ok()
+}
+
+/// Helper function to identify quote mint from pool tokens
+///
+/// `token_mint_0` and `token_mint_1` must be the pool's token A and token B
+/// mints, in that order.
+fn identify_quote_mint(
+    pool_state: &cp_amm::Pool,
+    token_mint_0: &Pubkey,
+    token_mint_1: &Pubkey,
+    quote_mint_rule: QuoteMintRule,
+) -> Result<Pubkey> {
+    if *token_mint_0 != pool_state.token_a_mint || *token_mint_1 != pool_state.token_b_mint {
+        return Err(DammHonoraryFeeError::InvalidPoolTokenOrder.into());
+    }
+
+    let pool_mints = [pool_state.token_a_mint, pool_state.token_b_mint];
+    let quote_mint = match quote_mint_rule {
+        QuoteMintRule::Explicit(mint) => pool_mints.contains(&mint).then_some(mint),
+        QuoteMintRule::KnownQuoteMints => quote_mints::KNOWN_QUOTE_MINTS
+            .into_iter()
+            .find(|mint| pool_mints.contains(mint)),
+    };
+
+    quote_mint.ok_or_else(|| DammHonoraryFeeError::InvalidPoolTokenOrder.into())
+}
+
+/// Helper function to verify the owner PDA controls a freshly created position
+fn verify_position_ownership(
+    position: &AccountInfo,
+    position_nft_account: &AccountInfo,
+    pool_key: &Pubkey,
+    position_nft_mint: &Pubkey,
+    owner_pda: &Pubkey,
+) -> Result<()> {
+    let position_state = cp_amm::Position::try_from_account_info(position)?;
+    if position_state.pool != *pool_key || position_state.nft_mint != *position_nft_mint {
+        return Err(DammHonoraryFeeError::InvalidPositionOwner.into());
+    }
+
+    // cp-amm mints position NFTs with Token-2022; SPL Token accounts share the layout
+    if position_nft_account.owner != &token_2022::ID && position_nft_account.owner != &token::ID {
+        return Err(DammHonoraryFeeError::InvalidPositionOwner.into());
+    }
+    let nft_account =
+        token_interface::TokenAccount::try_deserialize(&mut &position_nft_account.try_borrow_data()?[..])
+            .map_err(|_| DammHonoraryFeeError::InvalidPositionOwner)?;
+    if nft_account.owner != *owner_pda || nft_account.mint != *position_nft_mint || nft_account.amount != 1 {
+        return Err(DammHonoraryFeeError::InvalidPositionOwner.into());
+    }
+
+    Ok(())
+}
+
+/// Helper function to validate an investor quote ATA passed in remaining accounts
+fn validate_investor_quote_ata(
+    investor_ata: &AccountInfo,
+    investor: &InvestorAccount,
+    quote_mint: &Pubkey,
+) -> Result<TokenAccount> {
+    if investor_ata.key() != investor.investor_quote_ata
+        || investor_ata.owner != &Token::id()
+        || !investor_ata.is_writable
+    {
+        return Err(DammHonoraryFeeError::InvalidInvestorAta.into());
+    }
+
+    let token_account = TokenAccount::try_deserialize(&mut &investor_ata.try_borrow_data()?[..])
+        .map_err(|_| DammHonoraryFeeError::InvalidInvestorAta)?;
+    if token_account.mint != *quote_mint {
+        return Err(DammHonoraryFeeError::InvalidInvestorAta.into());
+    }
+
+    Ok(token_account)
+}
+
+/// Helper function to validate the quote ATA of every investor in a page
+fn validate_page_quote_atas(
+    remaining_accounts: &[AccountInfo],
+    investor_accounts: &[InvestorAccount],
+    quote_mint: &Pubkey,
+) -> Result<Vec<TokenAccount>> {
+    // Each investor passes [quote ATA, Streamflow stream, dust ledger] in remaining accounts
+    if remaining_accounts.len() != investor_accounts.len() * InvestorAccount::REMAINING_ACCOUNTS {
+        return Err(DammHonoraryFeeError::InvalidInvestorAta.into());
+    }
+
+    investor_accounts
+        .iter()
+        .zip(remaining_accounts.chunks(InvestorAccount::REMAINING_ACCOUNTS))
+        .map(|(investor, accounts)| validate_investor_quote_ata(&accounts[0], investor, quote_mint))
+        .collect()
+}
+
+/// Helper function to read the locked amount of every investor in a page at `snapshot_ts`
+fn read_page_locked_amounts(
+    remaining_accounts: &[AccountInfo],
+    investor_accounts: &[InvestorAccount],
+    quote_mint: &Pubkey,
+    base_mint: &Pubkey,
+    snapshot_ts: i64,
+) -> Result<Vec<u64>> {
+    let investor_atas = validate_page_quote_atas(remaining_accounts, investor_accounts, quote_mint)?;
+
+    let now = u64::try_from(snapshot_ts).map_err(|_| DammHonoraryFeeError::ArithmeticOverflow)?;
+    let mut locked_amounts = Vec::with_capacity(investor_accounts.len());
+    for ((investor, accounts), investor_ata) in investor_accounts
+        .iter()
+        .zip(remaining_accounts.chunks(InvestorAccount::REMAINING_ACCOUNTS))
+        .zip(&investor_atas)
+    {
+        locked_amounts.push(read_locked_amount(&accounts[1], investor, investor_ata, base_mint, now)?);
+    }
+
+    Ok(locked_amounts)
+}
+
+/// Helper function to fold a day left open past the day gate into the next one
+///
+/// Only called for a day not interrupted by a pause (see
+/// `ProgressAccount::opens_day`). Nothing of the day is lost when it is abandoned. In the snapshot pass its
+/// claimed fees return to the unassigned fees and its carry-in to the
+/// carry-over, so the next day distributes both. In the payout pass the
+/// investor pool share of the pages not yet paid joins the carry-over; the
+/// creator's share stays in the treasury for the next day's remainder.
+fn abandon_open_day(progress: &mut ProgressAccount, epoch_seconds: u32) -> Result<()> {
+    let (carried_out, reassigned_claim) = match progress.phase {
+        DistributionPhase::Snapshot => (progress.carried_in_today, progress.claimed_quote_today),
+        DistributionPhase::Payout => {
+            let unpaid_locked = progress.locked_total_today - progress.locked_paid_today;
+            let unpaid_pool = if progress.locked_total_today > 0 {
+                (progress.investor_pool_today as u128 * unpaid_locked as u128
+                    / progress.locked_total_today as u128) as u64
+            } else {
+                0
+            };
+            (unpaid_pool, 0)
+        }
+    };
+
+    progress.carry_over_lamports = progress.carry_over_lamports
+        .checked_add(carried_out)
+        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+    progress.unassigned_claim_lamports = progress.unassigned_claim_lamports
+        .checked_add(reassigned_claim)
+        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+
+    emit!(DayAbandoned {
+        day_id: progress.day_id,
+        phase: progress.phase,
+        carried_out,
+        reassigned_claim,
+        epoch_seconds,
+    });
+
+    Ok(())
+}
+
+/// Select the day a crank past the gate opens and assign its claimed fees
+///
+/// Epochs without a crank are reported once in a `DaysSkipped` event. In
+/// `MultiDay` mode the current epoch opens covering every epoch since the last
+/// day; in `Replay` mode the oldest missed epoch opens with an even share of
+/// the fees not yet assigned, and later cranks replay the rest in order.
+fn open_day(
+    progress: &mut ProgressAccount,
+    policy: &PolicyAccount,
+    current_time: i64,
+    claimed_quote: u64,
+) -> Result<()> {
+    let current_day_id = policy.epoch_id(current_time);
+    let last_day_id = if progress.day_state == DayState::Idle {
+        None
+    } else {
+        Some(policy.epoch_id(progress.last_distribution_ts))
+    };
+
+    if let Some(last_day_id) = last_day_id {
+        let first_skipped = last_day_id.max(policy.epoch_id(progress.last_opened_ts)) + 1;
+        if first_skipped < current_day_id {
+            emit!(DaysSkipped {
+                skipped_day_ids: (first_skipped..current_day_id)
+                    .take(MAX_LISTED_SKIPPED_DAYS)
+                    .collect(),
+                skipped_count: current_day_id - first_skipped,
+                catch_up_mode: policy.catch_up_mode,
+                epoch_seconds: policy.epoch_seconds,
+            });
+        }
+    }
+    progress.last_opened_ts = current_time;
+
+    let unassigned = progress.unassigned_claim_lamports
+        .checked_add(claimed_quote)
+        .ok_or(DammHonoraryFeeError::ArithmeticOverflow)?;
+
+    let (day_id, days_covered, claimed_today) = match (policy.catch_up_mode, last_day_id) {
+        (CatchUpMode::Replay, Some(last_day_id)) if last_day_id + 1 < current_day_id => {
+            // Split evenly over the replayed epoch and every one after it
+            let day_id = last_day_id + 1;
+            (day_id, 1, unassigned / (current_day_id - day_id + 1))
+        }
+        (CatchUpMode::MultiDay, Some(last_day_id)) => {
+            (current_day_id, current_day_id - last_day_id, unassigned)
+        }
+        _ => (current_day_id, 1, unassigned),
+    };
+
+    progress.day_id = day_id;
+    // A replayed day is evaluated as of its own start
+    progress.last_distribution_ts = if day_id == current_day_id {
+        current_time
+    } else {
+        policy.epoch_start(day_id)
+    };
+    progress.days_covered_today = days_covered;
+    progress.claimed_quote_today = claimed_today;
+    progress.unassigned_claim_lamports = unassigned - claimed_today;
+
+    Ok(())
+}
+
+fn investor_pool_for_day(claimed_quote: u64, locked_total: u64, policy: &PolicyAccount) -> u64 {
+    let y0 = policy.y0_total_allocation;
+    let f_locked_bps = if y0 > 0 {
+        std::cmp::min(locked_total as u128 * 10000 / y0 as u128, 10000) as u16
+    } else {
+        0
+    };
+
+    let eligible_investor_share_bps = std::cmp::min(policy.investor_fee_share_bps, f_locked_bps);
+
+    (claimed_quote as u128 * eligible_investor_share_bps as u128 / 10000) as u64
+}
+
+/// Helper function to load an investor's dust ledger, if one has been created
+fn load_dust_ledger<'info>(
+    ledger: &'info AccountInfo<'info>,
+    investor: &InvestorAccount,
+    policy_key: &Pubkey,
+) -> Result<Option<Account<'info, InvestorDustLedger>>> {
+    if ledger.owner != &crate::ID {
+        // No ledger yet, but the slot must still be this investor's ledger address
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"dust_ledger", policy_key.as_ref(), investor.stream_pubkey.as_ref()],
+            &crate::ID,
+        );
+        if ledger.key() != expected {
+            return Err(DammHonoraryFeeError::InvalidDustLedger.into());
+        }
+        return Ok(None);
+    }
+
+    let ledger = Account::<InvestorDustLedger>::try_from(ledger)?;
+    if ledger.policy_id != *policy_key || ledger.stream_pubkey != investor.stream_pubkey {
+        return Err(DammHonoraryFeeError::InvalidDustLedger.into());
+    }
+
+    Ok(Some(ledger))
+}
+
+/// Helper function to read an investor's still-locked amount from their Streamflow stream
+fn read_locked_amount(
+    stream: &AccountInfo,
+    investor: &InvestorAccount,
+    investor_ata: &TokenAccount,
+    base_mint: &Pubkey,
+    now: u64,
+) -> Result<u64> {
+    if stream.key() != investor.stream_pubkey {
+        return Err(DammHonoraryFeeError::StreamflowReadError.into());
+    }
+
+    // The stream must vest the pool's base token to the owner of the payout ATA
+    let contract = streamflow::Contract::try_from_account_info(stream)?;
+    if contract.mint != *base_mint || contract.recipient != investor_ata.owner {
+        return Err(DammHonoraryFeeError::StreamflowReadError.into());
+    }
+
+    Ok(contract.locked_amount(now))
+}
+
+/// Helper function to validate the pool only ever charges fees in the quote token
+///
+/// cp-amm's `OnlyB` mode charges every swap fee in token B, whichever way the
+/// price moves; `BothToken` charges the input token. There is no mode that
+/// charges only token A, so the quote mint must be token B.
+fn validate_quote_only_fee_mode(collect_fee_mode: u8, quote_is_token_a: bool) -> Result<()> {
+    if quote_is_token_a || collect_fee_mode != cp_amm::COLLECT_FEE_MODE_ONLY_B {
+        return Err(DammHonoraryFeeError::NotQuoteOnly.into());
+    }
+
+    Ok(())
+}
+
+/// Helper function to validate the pool's price range against its current price
+///
+/// Every cp-amm position spans the pool's `[sqrt_min_price, sqrt_max_price]`
+/// range. Liquidity holds only token A while the price sits at the bottom of
+/// the range and only token B once it reaches the top, so the price must sit
+/// at the quote token's end of the range when the position is created. This
+/// only holds at creation: once the price moves the position holds base
+/// liquidity, and it is the pool's fee mode that keeps its fees quote-only.
+fn validate_quote_only_position(
+    sqrt_min_price: u128,
+    sqrt_max_price: u128,
+    sqrt_price: u128,
+    quote_is_token_a: bool,
+) -> Result<()> {
+    if sqrt_min_price >= sqrt_max_price
+        || sqrt_min_price < cp_amm::MIN_SQRT_PRICE
+        || sqrt_max_price > cp_amm::MAX_SQRT_PRICE
+    {
+        return Err(DammHonoraryFeeError::InvalidTickRange.into());
+    }
+
+    let holds_only_quote = if quote_is_token_a {
+        sqrt_price <= sqrt_min_price
+    } else {
+        sqrt_price >= sqrt_max_price
+    };
+    if !holds_only_quote {
+        return Err(DammHonoraryFeeError::NotQuoteOnly.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote_only_error(sqrt_min_price: u128, sqrt_max_price: u128, sqrt_price: u128, quote_is_token_a: bool) -> Option<u32> {
+        validate_quote_only_position(sqrt_min_price, sqrt_max_price, sqrt_price, quote_is_token_a)
+            .err()
+            .map(|err| match err {
+                Error::AnchorError(err) => err.error_code_number,
+                Error::ProgramError(_) => u32::MAX,
+            })
+    }
+
+    fn code(err: DammHonoraryFeeError) -> Option<u32> {
+        Some(anchor_lang::error::ERROR_CODE_OFFSET + err as u32)
+    }
+
+    const LOW: u128 = 1 << 60;
+    const HIGH: u128 = 1 << 68;
+
+    #[test]
+    fn test_quote_token_a_requires_price_at_range_floor() {
+        assert_eq!(quote_only_error(LOW, HIGH, LOW, true), None);
+        assert_eq!(quote_only_error(LOW, HIGH, LOW + 1, true), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(quote_only_error(LOW, HIGH, 1 << 64, true), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(quote_only_error(LOW, HIGH, HIGH, true), code(DammHonoraryFeeError::NotQuoteOnly));
+    }
+
+    #[test]
+    fn test_quote_token_b_requires_price_at_range_ceiling() {
+        assert_eq!(quote_only_error(LOW, HIGH, HIGH, false), None);
+        assert_eq!(quote_only_error(LOW, HIGH, HIGH - 1, false), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(quote_only_error(LOW, HIGH, 1 << 64, false), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(quote_only_error(LOW, HIGH, LOW, false), code(DammHonoraryFeeError::NotQuoteOnly));
+    }
+
+    fn fee_mode_error(collect_fee_mode: u8, quote_is_token_a: bool) -> Option<u32> {
+        validate_quote_only_fee_mode(collect_fee_mode, quote_is_token_a)
+            .err()
+            .map(|err| match err {
+                Error::AnchorError(err) => err.error_code_number,
+                Error::ProgramError(_) => u32::MAX,
+            })
+    }
+
+    #[test]
+    fn test_quote_token_b_requires_only_b_fee_mode() {
+        assert_eq!(fee_mode_error(cp_amm::COLLECT_FEE_MODE_ONLY_B, false), None);
+        assert_eq!(fee_mode_error(cp_amm::COLLECT_FEE_MODE_BOTH_TOKEN, false), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(fee_mode_error(2, false), code(DammHonoraryFeeError::NotQuoteOnly));
+    }
+
+    #[test]
+    fn test_quote_token_a_has_no_quote_only_fee_mode() {
+        assert_eq!(fee_mode_error(cp_amm::COLLECT_FEE_MODE_ONLY_B, true), code(DammHonoraryFeeError::NotQuoteOnly));
+        assert_eq!(fee_mode_error(cp_amm::COLLECT_FEE_MODE_BOTH_TOKEN, true), code(DammHonoraryFeeError::NotQuoteOnly));
+    }
+
+    #[test]
+    fn test_sqrt_price_bounds() {
+        let (min, max) = (cp_amm::MIN_SQRT_PRICE, cp_amm::MAX_SQRT_PRICE);
+        assert_eq!(quote_only_error(min, max, min, true), None);
+        assert_eq!(quote_only_error(min, max, max, false), None);
+        assert_eq!(quote_only_error(min - 1, max, min - 1, true), code(DammHonoraryFeeError::InvalidTickRange));
+        assert_eq!(quote_only_error(min, max + 1, max + 1, false), code(DammHonoraryFeeError::InvalidTickRange));
+        assert_eq!(quote_only_error(LOW, LOW, LOW, true), code(DammHonoraryFeeError::InvalidTickRange));
+        assert_eq!(quote_only_error(HIGH, LOW, HIGH, true), code(DammHonoraryFeeError::InvalidTickRange));
+    }
+
+    #[test]
+    fn test_pool_fields_sit_at_cp_amm_offsets() {
+        use std::mem::{offset_of, size_of};
+        assert_eq!(offset_of!(cp_amm::Pool, token_a_mint), 160);
+        assert_eq!(offset_of!(cp_amm::Pool, token_b_mint), 192);
+        assert_eq!(offset_of!(cp_amm::Pool, token_a_vault), 224);
+        assert_eq!(offset_of!(cp_amm::Pool, token_b_vault), 256);
+        assert_eq!(offset_of!(cp_amm::Pool, liquidity), 352);
+        assert_eq!(offset_of!(cp_amm::Pool, sqrt_min_price), 416);
+        assert_eq!(offset_of!(cp_amm::Pool, sqrt_max_price), 432);
+        assert_eq!(offset_of!(cp_amm::Pool, sqrt_price), 448);
+        assert_eq!(offset_of!(cp_amm::Pool, collect_fee_mode), 476);
+        assert_eq!(size_of::<cp_amm::Pool>(), 480);
+    }
+
+    fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    #[test]
+    fn test_fully_populated_accounts_fit_allocation() {
+        let key = Pubkey::new_unique();
+        let params = PolicyParams {
+            creator_wallet: key,
+            investor_fee_share_bps: 10000,
+            daily_cap_lamports: Some(u64::MAX),
+            min_payout_lamports: u64::MAX,
+            epoch_seconds: u32::MAX,
+            epoch_offset_seconds: u32::MAX,
+            catch_up_mode: CatchUpMode::Replay,
+        };
+        let policy = PolicyAccount {
+            pool_id: key,
+            vault_pubkey: key,
+            creator_wallet: key,
+            quote_mint: key,
+            base_mint: key,
+            investor_fee_share_bps: 10000,
+            daily_cap_lamports: Some(u64::MAX),
+            min_payout_lamports: u64::MAX,
+            y0_total_allocation: u64::MAX,
+            epoch_seconds: u32::MAX,
+            epoch_offset_seconds: u32::MAX,
+            catch_up_mode: CatchUpMode::Replay,
+            roster_page_count: u32::MAX,
+            authority: key,
+            pending_authority: Some(key),
+            guardian: Some(key),
+            paused: true,
+            last_paused_ts: i64::MAX,
+            pending_params: Some(params),
+            pending_effective_day_id: u64::MAX,
+            bump: u8::MAX,
+        };
+        let honorary_position = HonoraryPositionAccount {
+            pool_id: key,
+            position_id: key,
+            position_nft_mint: key,
+            owner_pda: key,
+            quote_mint: key,
+            sqrt_min_price: u128::MAX,
+            sqrt_max_price: u128::MAX,
+            bump: u8::MAX,
+        };
+        let progress = ProgressAccount {
+            policy_id: key,
+            day_id: u64::MAX,
+            day_seq: u64::MAX,
+            last_distribution_ts: i64::MAX,
+            last_opened_ts: i64::MAX,
+            cumulative_distributed_today: u64::MAX,
+            carry_over_lamports: u64::MAX,
+            cursor_idx: u32::MAX,
+            day_state: DayState::Closed,
+            paid_pages: [u8::MAX; ProgressAccount::PAID_PAGES_BYTES],
+            phase: DistributionPhase::Payout,
+            claimed_quote_today: u64::MAX,
+            days_covered_today: u64::MAX,
+            unassigned_claim_lamports: u64::MAX,
+            locked_total_today: u64::MAX,
+            investor_pool_today: u64::MAX,
+            locked_paid_today: u64::MAX,
+            carried_in_today: u64::MAX,
+            capped_today: u64::MAX,
+            ledger_dust_lamports: u64::MAX,
+            bump: u8::MAX,
+        };
+
+        let roster_page = InvestorRoster {
+            policy_id: key,
+            page_index: u32::MAX,
+            entries: vec![
+                InvestorAccount { investor_quote_ata: key, stream_pubkey: key };
+                InvestorRoster::MAX_ENTRIES
+            ],
+            snapshot_day_seq: u64::MAX,
+            snapshot_locked: vec![u64::MAX; InvestorRoster::MAX_ENTRIES],
+            receipt_bump: u8::MAX,
+            bump: u8::MAX,
+        };
+
+        // Fully populated accounts use every byte but the reserve
+        assert_eq!(serialized_len(&policy), PolicyAccount::LEN - ACCOUNT_RESERVE);
+        assert_eq!(serialized_len(&honorary_position), HonoraryPositionAccount::LEN - ACCOUNT_RESERVE);
+        assert_eq!(serialized_len(&progress), ProgressAccount::LEN - ACCOUNT_RESERVE);
+        assert_eq!(serialized_len(&roster_page), InvestorRoster::LEN);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,22 @@
+//! Well-known quote mints accepted by `QuoteMintRule::KnownQuoteMints`
+
+use anchor_lang::prelude::*;
+
+/// USDC
+pub mod usdc {
+    use super::*;
+    declare_id!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+}
+
+/// USDT
+pub mod usdt {
+    use super::*;
+    declare_id!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB");
+}
+
+/// Quote mints in order of preference when a pool pairs two of them
+pub const KNOWN_QUOTE_MINTS: [Pubkey; 3] = [
+    usdc::ID,
+    usdt::ID,
+    anchor_spl::token::spl_token::native_mint::ID,
+];
@@ -2,8 +2,14 @@
 
 use anchor_lang::prelude::*;
 
+use crate::errors::DammHonoraryFeeError;
+
+/// Bytes allocated after an account's fields so future fields fit without a realloc
+pub const ACCOUNT_RESERVE: usize = 64;
+
 /// Policy configuration for fee distribution
 #[account]
+#[derive(InitSpace)]
 pub struct PolicyAccount {
     /// The pool this policy applies to
     pub pool_id: Pubkey,
@@ -13,6 +19,8 @@ pub struct PolicyAccount {
     pub creator_wallet: Pubkey,
     /// The quote mint for this pool
     pub quote_mint: Pubkey,
+    /// The pool's base mint, vested by the investors' streams
+    pub base_mint: Pubkey,
     /// The investor fee share in basis points (max 10000 = 100%)
     pub investor_fee_share_bps: u16,
     /// Daily cap on total distribution in lamports (optional)
@@ -21,12 +29,133 @@ pub struct PolicyAccount {
     pub min_payout_lamports: u64,
     /// Total investor allocation at TGE (Y0)
     pub y0_total_allocation: u64,
+    /// Length of a distribution epoch ("day") in seconds
+    pub epoch_seconds: u32,
+    /// Shift of epoch boundaries from the Unix epoch, e.g. to align to a timezone
+    pub epoch_offset_seconds: u32,
+    /// How epochs without a crank are distributed
+    pub catch_up_mode: CatchUpMode,
+    /// Number of investor roster pages; the day closes on the last one
+    pub roster_page_count: u32,
+    /// Key allowed to update the policy; `Pubkey::default()` once renounced
+    pub authority: Pubkey,
+    /// Key proposed as the next authority, pending its acceptance
+    pub pending_authority: Option<Pubkey>,
+    /// Optional key that may pause and resume the crank alongside the authority
+    pub guardian: Option<Pubkey>,
+    /// Whether the crank is halted
+    pub paused: bool,
+    /// When the crank was last paused (0 if never)
+    pub last_paused_ts: i64,
+    /// Parameters set by `update_policy`, applied from `pending_effective_day_id`
+    pub pending_params: Option<PolicyParams>,
+    /// First epoch the pending parameters apply to, under the current epoch length
+    pub pending_effective_day_id: u64,
     /// Bump for PDA
     pub bump: u8,
 }
 
+impl PolicyAccount {
+    /// Account size: discriminator + fields + reserve
+    pub const LEN: usize = 8 + Self::INIT_SPACE + ACCOUNT_RESERVE;
+
+    /// The parameters currently in force
+    pub fn params(&self) -> PolicyParams {
+        PolicyParams {
+            creator_wallet: self.creator_wallet,
+            investor_fee_share_bps: self.investor_fee_share_bps,
+            daily_cap_lamports: self.daily_cap_lamports,
+            min_payout_lamports: self.min_payout_lamports,
+            epoch_seconds: self.epoch_seconds,
+            epoch_offset_seconds: self.epoch_offset_seconds,
+            catch_up_mode: self.catch_up_mode,
+        }
+    }
+
+    /// Epoch ("day") containing `timestamp`
+    pub fn epoch_id(&self, timestamp: i64) -> u64 {
+        let shifted = timestamp.saturating_sub(self.epoch_offset_seconds as i64).max(0);
+        (shifted / self.epoch_seconds as i64) as u64
+    }
+
+    /// First timestamp of epoch `day_id`
+    pub fn epoch_start(&self, day_id: u64) -> i64 {
+        (day_id as i64)
+            .saturating_mul(self.epoch_seconds as i64)
+            .saturating_add(self.epoch_offset_seconds as i64)
+    }
+
+    /// Daily cap for a day standing in for `days` epochs
+    pub fn cap_for_days(&self, days: u64) -> Option<u64> {
+        self.daily_cap_lamports.map(|cap| cap.saturating_mul(days))
+    }
+
+    /// Apply pending parameters once their effective epoch has started
+    pub fn apply_pending_params(&mut self, timestamp: i64) {
+        let Some(params) = self.pending_params else {
+            return;
+        };
+        if self.epoch_id(timestamp) < self.pending_effective_day_id {
+            return;
+        }
+
+        self.creator_wallet = params.creator_wallet;
+        self.investor_fee_share_bps = params.investor_fee_share_bps;
+        self.daily_cap_lamports = params.daily_cap_lamports;
+        self.min_payout_lamports = params.min_payout_lamports;
+        self.epoch_seconds = params.epoch_seconds;
+        self.epoch_offset_seconds = params.epoch_offset_seconds;
+        self.catch_up_mode = params.catch_up_mode;
+        self.pending_params = None;
+    }
+}
+
+/// Policy parameters the authority can change after initialization
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyParams {
+    /// The creator wallet (receives remainder)
+    pub creator_wallet: Pubkey,
+    /// The investor fee share in basis points (max 10000 = 100%)
+    pub investor_fee_share_bps: u16,
+    /// Daily cap on total distribution in lamports (optional)
+    pub daily_cap_lamports: Option<u64>,
+    /// Minimum payout per investor in lamports
+    pub min_payout_lamports: u64,
+    /// Length of a distribution epoch ("day") in seconds
+    pub epoch_seconds: u32,
+    /// Shift of epoch boundaries from the Unix epoch, below `epoch_seconds`
+    pub epoch_offset_seconds: u32,
+    /// How epochs without a crank are distributed
+    pub catch_up_mode: CatchUpMode,
+}
+
+/// How the crank distributes epochs in which nobody cranked
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CatchUpMode {
+    /// Distribute everything accrued since the last day as one day, with the
+    /// daily cap scaled by the number of epochs it covers
+    MultiDay,
+    /// Run each missed epoch as its own day, oldest first, splitting the
+    /// claimed fees evenly across the missed epochs and the current one
+    Replay,
+}
+
+impl PolicyParams {
+    /// Check the parameters are within their allowed ranges
+    pub fn validate(&self) -> Result<()> {
+        if self.investor_fee_share_bps > 10000 {
+            return Err(DammHonoraryFeeError::InvalidFeeShareBps.into());
+        }
+        if self.epoch_seconds == 0 || self.epoch_offset_seconds >= self.epoch_seconds {
+            return Err(DammHonoraryFeeError::InvalidEpochLength.into());
+        }
+        Ok(())
+    }
+}
+
 /// Honorary position metadata
 #[account]
+#[derive(InitSpace)]
 pub struct HonoraryPositionAccount {
     /// The pool this position belongs to
     pub pool_id: Pubkey,
@@ -38,43 +167,287 @@ pub struct HonoraryPositionAccount {
     pub owner_pda: Pubkey,
     /// The quote mint for this pool
     pub quote_mint: Pubkey,
-    /// The tick range for this position
-    pub tick_lower: i32,
-    pub tick_upper: i32,
+    /// The price range (Q64.64) the position spans: the pool's full range
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
     /// Bump for PDA
     pub bump: u8,
 }
 
+impl HonoraryPositionAccount {
+    /// Account size: discriminator + fields + reserve
+    pub const LEN: usize = 8 + Self::INIT_SPACE + ACCOUNT_RESERVE;
+}
+
 /// Progress tracking for daily distribution
 #[account]
+#[derive(InitSpace)]
 pub struct ProgressAccount {
     /// The policy this progress tracks
     pub policy_id: Pubkey,
-    /// Current day ID (floor(timestamp / 86400))
+    /// Current day (epoch) ID, see `PolicyAccount::epoch_id`
     pub day_id: u64,
-    /// Last distribution timestamp
+    /// Number of days opened so far; unlike `day_id` it never repeats when
+    /// the epoch length or offset changes, so it keys per-day PDAs
+    pub day_seq: u64,
+    /// Timestamp the current day was opened; locked amounts are read as of it
     pub last_distribution_ts: i64,
-    /// Total distributed today in lamports
+    /// Time the crank last opened a day; later than `last_distribution_ts`
+    /// while missed days are replayed
+    pub last_opened_ts: i64,
+    /// Total distributed to investors today in lamports (bounded by the daily cap)
     pub cumulative_distributed_today: u64,
-    /// Carry over lamports for next distribution
+    /// Dust and capped excess carried out of today into the next day's pool
     pub carry_over_lamports: u64,
     /// Current page index for pagination
     pub cursor_idx: u32,
-    /// Whether this day's distribution is closed
-    pub is_closed: bool,
-    /// Page payout tracking (page_index -> total_paid_this_page)
-    pub page_payouts: std::collections::BTreeMap<u32, u64>,
+    /// Where the current day is in its lifecycle
+    pub day_state: DayState,
+    /// Bitmap of pages paid by today's payout pass, one bit per page index
+    pub paid_pages: [u8; ProgressAccount::PAID_PAGES_BYTES],
+    /// Which pass over the investor pages the current day is in
+    pub phase: DistributionPhase,
+    /// Quote fees assigned to the current day when it was opened
+    pub claimed_quote_today: u64,
+    /// Number of epochs the current day stands in for (above 1 for a multi-day catch-up)
+    pub days_covered_today: u64,
+    /// Claimed fees held in the treasury for missed days still to be replayed
+    pub unassigned_claim_lamports: u64,
+    /// Sum of locked amounts recorded by the snapshot pass
+    pub locked_total_today: u64,
+    /// Investor share of today's claim, frozen at the end of the snapshot pass
+    pub investor_pool_today: u64,
+    /// Sum of locked amounts covered so far by the payout pass
+    pub locked_paid_today: u64,
+    /// Carry-over from the previous day added to today's investor pool
+    pub carried_in_today: u64,
+    /// Part of today's investor pool withheld by the daily cap (included in the carry-over)
+    pub capped_today: u64,
+    /// Dust owed to investors through their dust ledgers, held in the treasury
+    pub ledger_dust_lamports: u64,
     /// Bump for PDA
     pub bump: u8,
 }
 
-/// Investor data for a distribution page
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// Pass over the investor pages within a day
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistributionPhase {
+    /// Accumulating locked amounts across all pages
+    Snapshot,
+    /// Paying investors against the frozen snapshot
+    Payout,
+}
+
+/// Lifecycle of the distribution day tracked by the progress PDA
+///
+/// Transitions, all made by `crank_distribute_page`:
+///
+/// - `Idle -> Open`: the first crank opens the first day.
+/// - `Open -> Closed`: the final payout page of the day has run.
+/// - `Closed -> Open`: the first crank past the day gate (the start of a
+///   later epoch) opens the next day: the current epoch, or the oldest missed
+///   one when replaying.
+/// - `Open -> Open` (abandon): the first crank past the day gate finds the
+///   day still open. The day is abandoned, emitting `DayAbandoned`: what it
+///   had not distributed is folded into the next day, which opens in the same
+///   crank. A day open when the crank was paused is never abandoned; it is
+///   resumed from its cursor and must close before the next day opens.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DayState {
+    /// No day has been opened yet
+    Idle,
+    /// Pages of `day_id` are being processed
+    Open,
+    /// `day_id` is fully distributed; pages are rejected until the next day
+    Closed,
+}
+
+impl ProgressAccount {
+    /// Maximum number of pages per day (one bit of `paid_pages` each)
+    pub const MAX_PAGES: u32 = 512;
+
+    /// Size of the `paid_pages` bitmap in bytes
+    pub const PAID_PAGES_BYTES: usize = Self::MAX_PAGES as usize / 8;
+
+    /// Account size: discriminator + fields + reserve
+    pub const LEN: usize = 8 + Self::INIT_SPACE + ACCOUNT_RESERVE;
+
+    /// Whether a crank at `timestamp` opens a new day
+    ///
+    /// The open day is located by its opening time rather than `day_id`, so
+    /// the comparison stays valid when the policy's epoch length changes. A
+    /// day gate passed since the last opening opens a day from any state,
+    /// abandoning a day still open, unless that day was interrupted by a
+    /// pause; once a replayed day closes, the next missed day opens right away.
+    pub fn opens_day(&self, policy: &PolicyAccount, timestamp: i64) -> bool {
+        let current_day_id = policy.epoch_id(timestamp);
+        self.day_state == DayState::Idle
+            || (current_day_id > policy.epoch_id(self.last_opened_ts)
+                && !self.interrupted_by_pause(policy))
+            || (self.day_state == DayState::Closed
+                && current_day_id > policy.epoch_id(self.last_distribution_ts))
+    }
+
+    /// Whether the crank was paused while the current day was open
+    ///
+    /// Such a day is resumed from `cursor_idx` after the pause, even past the
+    /// day gate, rather than abandoned.
+    pub fn interrupted_by_pause(&self, policy: &PolicyAccount) -> bool {
+        self.day_state == DayState::Open && policy.last_paused_ts >= self.last_opened_ts
+    }
+
+    /// Whether the payout pass has paid the page today
+    pub fn is_page_paid(&self, page_index: u32) -> bool {
+        let (byte, bit) = ((page_index / 8) as usize, page_index % 8);
+        self.paid_pages.get(byte).is_some_and(|b| b & (1 << bit) != 0)
+    }
+
+    /// Record that the payout pass has paid the page today
+    pub fn mark_page_paid(&mut self, page_index: u32) {
+        let (byte, bit) = ((page_index / 8) as usize, page_index % 8);
+        self.paid_pages[byte] |= 1 << bit;
+    }
+}
+
+/// How the quote mint is selected from the pool's token pair
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteMintRule {
+    /// Use this mint, which must be one of the pool's tokens
+    Explicit(Pubkey),
+    /// Use the pool token found first in `quote_mints::KNOWN_QUOTE_MINTS`
+    KnownQuoteMints,
+}
+
+/// Investor entry of a roster page
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
 pub struct InvestorAccount {
     /// The investor's quote ATA
     pub investor_quote_ata: Pubkey,
-    /// The Streamflow stream pubkey for this investor
+    /// The Streamflow stream pubkey for this investor (locked amount is read on-chain)
+    pub stream_pubkey: Pubkey,
+}
+
+impl InvestorAccount {
+    /// Remaining accounts per investor: [quote ATA, Streamflow stream, dust ledger]
+    pub const REMAINING_ACCOUNTS: usize = 3;
+}
+
+/// Maximum investors per roster page, as a free constant for `max_len`
+pub const MAX_ROSTER_ENTRIES: usize = 12;
+
+/// One page of a policy's investor roster
+///
+/// Pages are cranked in `page_index` order and each processes exactly the
+/// investors listed here, so the cranker cannot omit, duplicate or reorder
+/// investors. Pages are created and edited by the policy authority. The
+/// snapshot pass records each entry's locked amount on the page, and the
+/// payout pass pays from those amounts rather than re-reading the streams.
+#[account]
+#[derive(InitSpace)]
+pub struct InvestorRoster {
+    /// The policy this page belongs to
+    pub policy_id: Pubkey,
+    /// Position of the page in the roster
+    pub page_index: u32,
+    /// Investors paid by this page, in payout order
+    #[max_len(MAX_ROSTER_ENTRIES)]
+    pub entries: Vec<InvestorAccount>,
+    /// Day (`ProgressAccount::day_seq`) whose snapshot pass recorded `snapshot_locked`
+    pub snapshot_day_seq: u64,
+    /// Locked amount of each entry at the snapshot, in entry order
+    #[max_len(MAX_ROSTER_ENTRIES)]
+    pub snapshot_locked: Vec<u64>,
+    /// Bump of the page's receipt PDA for `snapshot_day_seq`
+    pub receipt_bump: u8,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl InvestorRoster {
+    /// Maximum investors per page, bounded by the accounts a crank transaction can lock
+    pub const MAX_ENTRIES: usize = MAX_ROSTER_ENTRIES;
+
+    /// Account size: discriminator + fields, with room for `MAX_ENTRIES` entries
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Marker that a stream is listed on a policy's roster, one per policy and stream
+///
+/// Created when the stream is added to a page and closed when it is removed,
+/// so a stream is listed on at most one page of the roster.
+#[account]
+#[derive(InitSpace)]
+pub struct RosterEntry {
+    /// The policy whose roster lists the stream
+    pub policy_id: Pubkey,
+    /// The investor's Streamflow stream
     pub stream_pubkey: Pubkey,
-    /// Locked amount for this investor (queried from Streamflow)
-    pub locked_amount: u64,
-}
\ No newline at end of file
+    /// The roster page listing the stream
+    pub page_index: u32,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl RosterEntry {
+    /// Account size: discriminator + fields (closed on removal, so no reserve)
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Seeds of the marker PDA, without the bump
+    pub fn seeds<'a>(policy: &'a Pubkey, stream: &'a Pubkey) -> [&'a [u8]; 3] {
+        [b"roster_entry", policy.as_ref(), stream.as_ref()]
+    }
+}
+
+/// Dust owed to a single investor, accrued across days
+#[account]
+#[derive(InitSpace)]
+pub struct InvestorDustLedger {
+    /// The policy this ledger belongs to
+    pub policy_id: Pubkey,
+    /// The investor's Streamflow stream
+    pub stream_pubkey: Pubkey,
+    /// Accrued payouts below the minimum payout, not yet transferred
+    pub owed_lamports: u64,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl InvestorDustLedger {
+    /// Account size: discriminator + fields (one per investor, so no reserve)
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+}
+
+/// Proof that a payout page was paid, one per policy, day and page
+///
+/// The payout pass refuses a page whose receipt exists, so a resubmitted
+/// transaction cannot pay twice even if the progress PDA were rolled back.
+/// Receipts are keyed by `ProgressAccount::day_seq`, so a receipt left open
+/// never collides with a later day that reuses its `day_id`.
+#[account]
+#[derive(InitSpace)]
+pub struct PageReceipt {
+    /// The policy the page was paid under
+    pub policy_id: Pubkey,
+    /// Day the page was paid in
+    pub day_id: u64,
+    /// Sequence number of that day, see `ProgressAccount::day_seq`
+    pub day_seq: u64,
+    /// Index of the paid page
+    pub page_index: u32,
+    /// Cranker who paid the rent, refunded when the receipt is closed
+    pub payer: Pubkey,
+    /// Quote amount transferred to investors by the page
+    pub paid_lamports: u64,
+    /// Bump for PDA
+    pub bump: u8,
+}
+
+impl PageReceipt {
+    /// Account size: discriminator + fields (closed after the day, so no reserve)
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// Seeds of the receipt PDA, without the bump
+    pub fn seeds<'a>(policy: &'a Pubkey, day_seq: &'a [u8; 8], page_index: &'a [u8; 4]) -> [&'a [u8]; 4] {
+        [b"page_receipt", policy.as_ref(), day_seq, page_index]
+    }
+}
@@ -0,0 +1,94 @@
+//! Read-only view of Streamflow stream (contract) accounts
+
+use anchor_lang::prelude::*;
+
+use crate::errors::DammHonoraryFeeError;
+
+declare_id!("strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m");
+
+/// Stream creation parameters, as stored inside a Streamflow contract
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreateParams {
+    pub start_time: u64,
+    pub net_amount_deposited: u64,
+    pub period: u64,
+    pub amount_per_period: u64,
+    pub cliff: u64,
+    pub cliff_amount: u64,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub automatic_withdrawal: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub can_topup: bool,
+    pub stream_name: [u8; 64],
+    pub withdraw_frequency: u64,
+}
+
+/// Leading fields of a Streamflow contract account (Borsh, no discriminator)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Contract {
+    pub magic: u64,
+    pub version: u8,
+    pub created_at: u64,
+    pub amount_withdrawn: u64,
+    pub canceled_at: u64,
+    pub end_time: u64,
+    pub last_withdrawn_at: u64,
+    pub sender: Pubkey,
+    pub sender_tokens: Pubkey,
+    pub recipient: Pubkey,
+    pub recipient_tokens: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_tokens: Pubkey,
+    pub streamflow_treasury: Pubkey,
+    pub streamflow_treasury_tokens: Pubkey,
+    pub streamflow_fee_total: u64,
+    pub streamflow_fee_withdrawn: u64,
+    pub streamflow_fee_percent: f32,
+    pub partner: Pubkey,
+    pub partner_tokens: Pubkey,
+    pub partner_fee_total: u64,
+    pub partner_fee_withdrawn: u64,
+    pub partner_fee_percent: f32,
+    pub ix: CreateParams,
+}
+
+impl Contract {
+    /// Decode a stream account, checking it is owned by the Streamflow program
+    pub fn try_from_account_info(stream: &AccountInfo) -> Result<Self> {
+        if stream.owner != &ID {
+            return Err(DammHonoraryFeeError::StreamflowReadError.into());
+        }
+
+        let data = stream.try_borrow_data()?;
+        Self::deserialize(&mut &data[..])
+            .map_err(|_| DammHonoraryFeeError::StreamflowReadError.into())
+    }
+
+    /// Amount unlocked by the vesting schedule at `now`, whether withdrawn or not
+    pub fn unlocked_amount(&self, now: u64) -> u64 {
+        let deposited = self.ix.net_amount_deposited;
+        if now < self.ix.cliff {
+            return 0;
+        }
+        if now >= self.end_time || self.ix.period == 0 {
+            return deposited;
+        }
+
+        let periods_elapsed = (now - self.ix.cliff) / self.ix.period;
+        let streamed = (periods_elapsed as u128 * self.ix.amount_per_period as u128)
+            .saturating_add(self.ix.cliff_amount as u128);
+        std::cmp::min(streamed, deposited as u128) as u64
+    }
+
+    /// Amount still locked in the stream at `now`
+    pub fn locked_amount(&self, now: u64) -> u64 {
+        // A canceled stream returns its locked balance to the sender from
+        // the cancellation onwards
+        if self.canceled_at > 0 && self.canceled_at <= now {
+            return 0;
+        }
+        self.ix.net_amount_deposited.saturating_sub(self.unlocked_amount(now))
+    }
+}
@@ -1,36 +1,1034 @@
 //! Test helpers for DAMM Honorary Fee Module
 
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
 use solana_program_test::*;
 use solana_sdk::{
-    signature::Keypair,
+    account::AccountSharedData,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
     pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
 };
 
-/// Create a test context with all necessary accounts
+use damm_honorary_fee::state::*;
+
+/// Default policy parameters used by `TestEnv::initialize`
+pub const INVESTOR_FEE_SHARE_BPS: u16 = 5000;
+pub const DAILY_CAP_LAMPORTS: Option<u64> = Some(1_000_000_000);
+pub const MIN_PAYOUT_LAMPORTS: u64 = 100_000;
+pub const Y0_TOTAL_ALLOCATION: u64 = 1_000_000_000;
+pub const EPOCH_SECONDS: u32 = 86400;
+pub const EPOCH_OFFSET_SECONDS: u32 = 0;
+pub const CATCH_UP_MODE: CatchUpMode = CatchUpMode::MultiDay;
+
+/// Price range of the test pool (Q64.64); its price starts at the quote end
+/// of the range, so positions hold only the quote token
+pub const POOL_SQRT_MIN_PRICE: u128 = 1 << 60;
+pub const POOL_SQRT_MAX_PRICE: u128 = 1 << 68;
+
+fn process_damm_honorary_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    damm_honorary_fee::entry(program_id, accounts, data)
+}
+
+fn process_cp_amm_mock(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    cp_amm_mock::entry(program_id, accounts, data)
+}
+
+fn process_streamflow_mock(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    streamflow_mock::entry(program_id, accounts, data)
+}
+
+/// Create a test context with all necessary programs loaded
 pub async fn create_test_context() -> ProgramTestContext {
-    let program_test = ProgramTest::new(
+    let mut program_test = ProgramTest::new(
         "damm_honorary_fee",
         damm_honorary_fee::ID,
-        None,
+        processor!(process_damm_honorary_fee),
+    );
+    program_test.add_program("cp_amm_mock", cp_amm_mock::ID, processor!(process_cp_amm_mock));
+    program_test.add_program(
+        "streamflow_mock",
+        streamflow_mock::ID,
+        processor!(process_streamflow_mock),
     );
 
-    program_test.start().await
+    program_test.start_with_context().await
 }
 
 /// Generate a deterministic pubkey for testing
 pub fn test_pubkey(seed: &str) -> Pubkey {
-    use solana_sdk::hash::{Hash, Hasher};
+    use solana_sdk::hash::Hasher;
     let mut hasher = Hasher::default();
     hasher.hash(seed.as_bytes());
     Pubkey::new_from_array(hasher.result().to_bytes())
 }
 
-/// Create test token mints
-pub async fn create_test_mints(context: &mut ProgramTestContext) -> (Keypair, Keypair) {
-    let quote_mint = Keypair::new();
-    let base_mint = Keypair::new();
+/// Sign and process a transaction paid for by the context payer
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers: Vec<&Keypair> = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+/// Fetch and deserialize an Anchor account
+pub async fn get_account<T: AccountDeserialize>(
+    context: &mut ProgramTestContext,
+    address: Pubkey,
+) -> T {
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    T::try_deserialize(&mut account.data.as_ref()).unwrap()
+}
+
+/// Write an Anchor account directly into the bank
+pub fn set_anchor_account<T: anchor_lang::AccountSerialize>(
+    context: &mut ProgramTestContext,
+    address: Pubkey,
+    owner: Pubkey,
+    value: &T,
+    space: usize,
+) {
+    let mut data = Vec::with_capacity(space);
+    value.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    let mut account = AccountSharedData::new(1_000_000_000, space, &owner);
+    account.set_data_from_slice(&data);
+    context.set_account(&address, &account);
+}
+
+/// Create an SPL token mint with the context payer as authority
+pub async fn create_mint(context: &mut ProgramTestContext, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let payer = context.payer.pubkey();
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, None, decimals)
+            .unwrap(),
+    ];
+    process(context, &instructions, &[&mint]).await.unwrap();
+    mint.pubkey()
+}
+
+/// Create an SPL token mint at a fixed address with the context payer as authority
+pub fn create_mint_at(context: &mut ProgramTestContext, address: Pubkey, decimals: u8) {
+    let mint = spl_token::state::Mint {
+        mint_authority: Some(context.payer.pubkey()).into(),
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(mint, &mut data).unwrap();
+    let mut account = AccountSharedData::new(1_000_000_000, data.len(), &spl_token::ID);
+    account.set_data_from_slice(&data);
+    context.set_account(&address, &account);
+}
+
+/// Create an SPL token account for `mint` owned by `owner`
+pub async fn create_token_account(
+    context: &mut ProgramTestContext,
+    mint: Pubkey,
+    owner: Pubkey,
+) -> Pubkey {
+    let account = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let payer = context.payer.pubkey();
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &account.pubkey(),
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), &mint, &owner)
+            .unwrap(),
+    ];
+    process(context, &instructions, &[&account]).await.unwrap();
+    account.pubkey()
+}
+
+/// Mint tokens to a token account (context payer is the mint authority)
+pub async fn mint_to(context: &mut ProgramTestContext, mint: Pubkey, to: Pubkey, amount: u64) {
+    let payer = context.payer.pubkey();
+    let ix = spl_token::instruction::mint_to(&spl_token::ID, &mint, &to, &payer, &[], amount).unwrap();
+    process(context, &[ix], &[]).await.unwrap();
+}
+
+/// Read the balance of a token account
+pub async fn token_balance(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+/// Current cluster unix timestamp
+pub async fn current_time(context: &mut ProgramTestContext) -> i64 {
+    let clock: solana_sdk::clock::Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+/// Move the cluster clock forward by `seconds`
+pub async fn advance_time(context: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: solana_sdk::clock::Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+/// Extract the custom program error code from a failed transaction
+pub fn custom_error_code(err: &TransactionError) -> Option<u32> {
+    match err {
+        TransactionError::InstructionError(_, solana_sdk::instruction::InstructionError::Custom(code)) => {
+            Some(*code)
+        }
+        _ => None,
+    }
+}
+
+/// Anchor error code for a program error variant
+pub fn error_code(err: damm_honorary_fee::errors::DammHonoraryFeeError) -> u32 {
+    anchor_lang::error::ERROR_CODE_OFFSET + err as u32
+}
+
+/// A cp-amm pool with an honorary position owned by the program PDA
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    pub quote_mint: Pubkey,
+    pub base_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub quote_mint_rule: QuoteMintRule,
+    pub pool: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub position: Pubkey,
+    pub position_nft_mint: Keypair,
+    pub position_nft_account: Pubkey,
+    pub vault_pubkey: Pubkey,
+    pub creator_wallet: Keypair,
+    pub owner_pda: Pubkey,
+    pub policy_pda: Pubkey,
+    pub honorary_position_pda: Pubkey,
+    pub progress_pda: Pubkey,
+    pub program_quote_treasury_ata: Pubkey,
+    pub program_base_treasury_ata: Pubkey,
+    pub creator_quote_ata: Pubkey,
+    pub funder_quote: Pubkey,
+    pub funder_base: Pubkey,
+}
+
+impl TestEnv {
+    /// Set up mints, a cp-amm pool (quote as token B, fees in token B only)
+    /// and a position owned by the program PDA
+    pub async fn new() -> Self {
+        let mut context = create_test_context().await;
+        let quote_mint = create_mint(&mut context, 6).await;
+        let base_mint = create_mint(&mut context, 6).await;
+        Self::with_pool(context, quote_mint, base_mint, false).await
+    }
+
+    /// Set up a cp-amm pool for existing mints, in the given token order, and a position owned by the program PDA
+    pub async fn with_pool(
+        mut context: ProgramTestContext,
+        quote_mint: Pubkey,
+        base_mint: Pubkey,
+        quote_is_token_a: bool,
+    ) -> Self {
+        let (token_a_mint, token_b_mint) = if quote_is_token_a {
+            (quote_mint, base_mint)
+        } else {
+            (base_mint, quote_mint)
+        };
+
+        let pool = Keypair::new();
+        let pool_authority = Pubkey::find_program_address(&[b"pool_authority"], &cp_amm_mock::ID).0;
+        let token_a_vault = Pubkey::find_program_address(
+            &[b"token_vault", token_a_mint.as_ref(), pool.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let token_b_vault = Pubkey::find_program_address(
+            &[b"token_vault", token_b_mint.as_ref(), pool.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let initialize_pool_ix = Instruction {
+            program_id: cp_amm_mock::ID,
+            accounts: cp_amm_mock::accounts::InitializePool {
+                pool: pool.pubkey(),
+                pool_authority,
+                token_a_mint,
+                token_b_mint,
+                token_a_vault,
+                token_b_vault,
+                payer: context.payer.pubkey(),
+                token_program: spl_token::ID,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: cp_amm_mock::instruction::InitializePool {
+                sqrt_min_price: POOL_SQRT_MIN_PRICE,
+                sqrt_max_price: POOL_SQRT_MAX_PRICE,
+                sqrt_price: if quote_is_token_a { POOL_SQRT_MIN_PRICE } else { POOL_SQRT_MAX_PRICE },
+                collect_fee_mode: 1,
+            }
+            .data(),
+        };
+        process(&mut context, &[initialize_pool_ix], &[&pool]).await.unwrap();
+
+        let vault_pubkey = Pubkey::new_unique();
+        let owner_pda = Pubkey::find_program_address(
+            &[b"vault", vault_pubkey.as_ref(), b"investor_fee_pos_owner"],
+            &damm_honorary_fee::ID,
+        )
+        .0;
+
+        let position_nft_mint = Keypair::new();
+        let position_nft_account = Pubkey::find_program_address(
+            &[b"position_nft_account", position_nft_mint.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let position = Pubkey::find_program_address(
+            &[b"position", position_nft_mint.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let creator_wallet = Keypair::new();
+        let fund_creator_ix = system_instruction::transfer(
+            &context.payer.pubkey(),
+            &creator_wallet.pubkey(),
+            10_000_000_000,
+        );
+        process(&mut context, &[fund_creator_ix], &[]).await.unwrap();
+
+        let policy_pda =
+            Pubkey::find_program_address(&[b"policy", pool.pubkey().as_ref()], &damm_honorary_fee::ID).0;
+        let honorary_position_pda = Pubkey::find_program_address(
+            &[b"honorary_position", pool.pubkey().as_ref()],
+            &damm_honorary_fee::ID,
+        )
+        .0;
+        let progress_pda =
+            Pubkey::find_program_address(&[b"progress", policy_pda.as_ref()], &damm_honorary_fee::ID).0;
+        let program_quote_treasury_ata =
+            spl_associated_token_account::get_associated_token_address(&owner_pda, &quote_mint);
 
-    // TODO: Actually create the mints using SPL token program
+        let program_base_treasury_ata =
+            spl_associated_token_account::get_associated_token_address(&owner_pda, &base_mint);
+        let payer = context.payer.pubkey();
+        let create_creator_ata_ix =
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &payer,
+                &creator_wallet.pubkey(),
+                &quote_mint,
+                &spl_token::ID,
+            );
+        process(&mut context, &[create_creator_ata_ix], &[]).await.unwrap();
+        let creator_quote_ata =
+            spl_associated_token_account::get_associated_token_address(&creator_wallet.pubkey(), &quote_mint);
+        let funder_quote = create_token_account(&mut context, quote_mint, payer).await;
+        let funder_base = create_token_account(&mut context, base_mint, payer).await;
+        mint_to(&mut context, quote_mint, funder_quote, 1_000_000_000_000).await;
+        mint_to(&mut context, base_mint, funder_base, 1_000_000_000_000).await;
 
-    (quote_mint, base_mint)
-}
\ No newline at end of file
+        Self {
+            context,
+            quote_mint,
+            base_mint,
+            token_a_mint,
+            token_b_mint,
+            quote_mint_rule: QuoteMintRule::Explicit(quote_mint),
+            pool: pool.pubkey(),
+            token_a_vault,
+            token_b_vault,
+            position,
+            position_nft_mint,
+            position_nft_account,
+            vault_pubkey,
+            creator_wallet,
+            owner_pda,
+            policy_pda,
+            honorary_position_pda,
+            progress_pda,
+            program_quote_treasury_ata,
+            program_base_treasury_ata,
+            creator_quote_ata,
+            funder_quote,
+            funder_base,
+        }
+    }
+
+    /// Overwrite the pool's price range and current price (Q64.64)
+    pub async fn set_pool_price(&mut self, sqrt_min_price: u128, sqrt_max_price: u128, sqrt_price: u128) {
+        let account = self.context.banks_client.get_account(self.pool).await.unwrap().unwrap();
+        let mut data = account.data.clone();
+        let mut account = AccountSharedData::from(account);
+        for (offset, value) in [
+            (std::mem::offset_of!(cp_amm_mock::Pool, sqrt_min_price), sqrt_min_price),
+            (std::mem::offset_of!(cp_amm_mock::Pool, sqrt_max_price), sqrt_max_price),
+            (std::mem::offset_of!(cp_amm_mock::Pool, sqrt_price), sqrt_price),
+        ] {
+            data[8 + offset..8 + offset + 16].copy_from_slice(&value.to_le_bytes());
+        }
+        account.set_data_from_slice(&data);
+        self.context.set_account(&self.pool, &account);
+    }
+
+    /// Overwrite the pool's fee collection mode
+    pub async fn set_pool_collect_fee_mode(&mut self, collect_fee_mode: u8) {
+        let account = self.context.banks_client.get_account(self.pool).await.unwrap().unwrap();
+        let mut data = account.data.clone();
+        let mut account = AccountSharedData::from(account);
+        data[8 + std::mem::offset_of!(cp_amm_mock::Pool, collect_fee_mode)] = collect_fee_mode;
+        account.set_data_from_slice(&data);
+        self.context.set_account(&self.pool, &account);
+    }
+
+    /// Build the `initialize_honorary_position` instruction
+    pub fn initialize_ix(&self) -> Instruction {
+        self.initialize_ix_with(|_| {})
+    }
+
+    /// Build the `initialize_honorary_position` instruction, adjusting its arguments
+    pub fn initialize_ix_with(
+        &self,
+        adjust: impl FnOnce(&mut damm_honorary_fee::instruction::InitializeHonoraryPosition),
+    ) -> Instruction {
+        let mut args = damm_honorary_fee::instruction::InitializeHonoraryPosition {
+            pool_id: self.pool,
+            vault_pubkey: self.vault_pubkey,
+            investor_fee_share_bps: INVESTOR_FEE_SHARE_BPS,
+            daily_cap_lamports: DAILY_CAP_LAMPORTS,
+            min_payout_lamports: MIN_PAYOUT_LAMPORTS,
+            y0_total_allocation: Y0_TOTAL_ALLOCATION,
+            epoch_seconds: EPOCH_SECONDS,
+            epoch_offset_seconds: EPOCH_OFFSET_SECONDS,
+            catch_up_mode: CATCH_UP_MODE,
+            quote_mint_rule: self.quote_mint_rule,
+        };
+        adjust(&mut args);
+
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::InitializeHonoraryPosition {
+                pool: self.pool,
+                token_mint_0: self.token_a_mint,
+                token_mint_1: self.token_b_mint,
+                position: self.position,
+                position_nft_mint: self.position_nft_mint.pubkey(),
+                position_nft_account: self.position_nft_account,
+                investor_fee_position_owner_pda: self.owner_pda,
+                vault_pubkey: self.vault_pubkey,
+                creator_wallet: self.creator_wallet.pubkey(),
+                policy_pda: self.policy_pda,
+                honorary_position: self.honorary_position_pda,
+                program_quote_treasury_ata: self.program_quote_treasury_ata,
+                program_base_treasury_ata: self.program_base_treasury_ata,
+                quote_mint: self.quote_mint,
+                base_mint: self.base_mint,
+                token_program: spl_token::ID,
+                token_2022_program: anchor_spl::token_2022::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: solana_sdk::system_program::ID,
+                pool_authority: Pubkey::find_program_address(&[b"pool_authority"], &cp_amm_mock::ID).0,
+                event_authority: Pubkey::find_program_address(&[b"__event_authority"], &cp_amm_mock::ID).0,
+                cp_amm_program: cp_amm_mock::ID,
+            }
+            .to_account_metas(None),
+            data: args.data(),
+        }
+    }
+
+    /// Process an `initialize_honorary_position` instruction signed by the creator and position NFT mint
+    pub async fn process_initialize(&mut self, ix: Instruction) -> Result<(), TransactionError> {
+        let creator_wallet = self.creator_wallet.insecure_clone();
+        let position_nft_mint = self.position_nft_mint.insecure_clone();
+        process(&mut self.context, &[ix], &[&creator_wallet, &position_nft_mint]).await
+    }
+
+    /// Create a cp-amm position owned by `owner`, outside the honorary fee program
+    pub async fn create_position(&mut self, owner: Pubkey) -> (Pubkey, Pubkey) {
+        let position_nft_mint = Keypair::new();
+        let position_nft_account = Pubkey::find_program_address(
+            &[b"position_nft_account", position_nft_mint.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let position = Pubkey::find_program_address(
+            &[b"position", position_nft_mint.pubkey().as_ref()],
+            &cp_amm_mock::ID,
+        )
+        .0;
+        let ix = Instruction {
+            program_id: cp_amm_mock::ID,
+            accounts: cp_amm_mock::accounts::CreatePosition {
+                owner,
+                position_nft_mint: position_nft_mint.pubkey(),
+                position_nft_account,
+                pool: self.pool,
+                position,
+                pool_authority: Pubkey::find_program_address(&[b"pool_authority"], &cp_amm_mock::ID).0,
+                payer: self.context.payer.pubkey(),
+                token_program: anchor_spl::token_2022::ID,
+                system_program: solana_sdk::system_program::ID,
+                event_authority: Pubkey::find_program_address(&[b"__event_authority"], &cp_amm_mock::ID).0,
+                program: cp_amm_mock::ID,
+            }
+            .to_account_metas(None),
+            data: cp_amm_mock::instruction::CreatePosition {}.data(),
+        };
+        process(&mut self.context, &[ix], &[&position_nft_mint]).await.unwrap();
+        (position, position_nft_account)
+    }
+
+    /// Build the `initialize_progress` instruction
+    pub fn initialize_progress_ix(&self) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::InitializeProgress {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                payer: self.context.payer.pubkey(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::InitializeProgress {}.data(),
+        }
+    }
+
+    /// Initialize the honorary position and its progress PDA
+    pub async fn initialize(&mut self) {
+        self.initialize_with(|_| {}).await;
+    }
+
+    /// Initialize with adjusted instruction arguments and create the progress PDA
+    pub async fn initialize_with(
+        &mut self,
+        adjust: impl FnOnce(&mut damm_honorary_fee::instruction::InitializeHonoraryPosition),
+    ) {
+        let ix = self.initialize_ix_with(adjust);
+        self.process_initialize(ix).await.unwrap();
+
+        let ix = self.initialize_progress_ix();
+        process(&mut self.context, &[ix], &[]).await.unwrap();
+    }
+
+    /// Credit fees to the honorary position as if swaps had occurred
+    pub async fn accrue_fees(&mut self, quote_fee: u64, base_fee: u64) {
+        let (funder_token_a, funder_token_b, fee_a, fee_b) = if self.token_a_mint == self.quote_mint {
+            (self.funder_quote, self.funder_base, quote_fee, base_fee)
+        } else {
+            (self.funder_base, self.funder_quote, base_fee, quote_fee)
+        };
+        let ix = Instruction {
+            program_id: cp_amm_mock::ID,
+            accounts: cp_amm_mock::accounts::AccruePositionFee {
+                pool: self.pool,
+                position: self.position,
+                token_a_vault: self.token_a_vault,
+                token_b_vault: self.token_b_vault,
+                funder_token_a,
+                funder_token_b,
+                funder: self.context.payer.pubkey(),
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: cp_amm_mock::instruction::AccruePositionFee { fee_a, fee_b }.data(),
+        };
+        process(&mut self.context, &[ix], &[]).await.unwrap();
+    }
+
+    /// Create a Streamflow stream of the base token for `recipient`
+    pub async fn create_stream(
+        &mut self,
+        recipient: Pubkey,
+        start_time: u64,
+        net_amount_deposited: u64,
+        period: u64,
+        amount_per_period: u64,
+    ) -> Pubkey {
+        let stream = Keypair::new();
+        let ix = Instruction {
+            program_id: streamflow_mock::ID,
+            accounts: streamflow_mock::accounts::CreateStream {
+                stream: stream.pubkey(),
+                sender: self.context.payer.pubkey(),
+                recipient,
+                mint: self.base_mint,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: streamflow_mock::instruction::CreateStream {
+                start_time,
+                net_amount_deposited,
+                period,
+                amount_per_period,
+                cliff_amount: 0,
+            }
+            .data(),
+        };
+        process(&mut self.context, &[ix], &[&stream]).await.unwrap();
+        stream.pubkey()
+    }
+
+    /// Cancel a Streamflow stream created by `create_stream`
+    pub async fn cancel_stream(&mut self, stream: Pubkey) {
+        let ix = Instruction {
+            program_id: streamflow_mock::ID,
+            accounts: streamflow_mock::accounts::CancelStream {
+                stream,
+                sender: self.context.payer.pubkey(),
+            }
+            .to_account_metas(None),
+            data: streamflow_mock::instruction::CancelStream {}.data(),
+        };
+        process(&mut self.context, &[ix], &[]).await.unwrap();
+    }
+
+    /// Create an investor whose stream keeps `locked_amount` fully locked for a year
+    pub async fn create_investor(&mut self, locked_amount: u64) -> InvestorAccount {
+        let wallet = Pubkey::new_unique();
+        let investor_quote_ata = create_token_account(&mut self.context, self.quote_mint, wallet).await;
+        let start_time = current_time(&mut self.context).await as u64 + 365 * 86400;
+        let stream_pubkey = self
+            .create_stream(wallet, start_time, locked_amount, 86400, locked_amount)
+            .await;
+        InvestorAccount {
+            investor_quote_ata,
+            stream_pubkey,
+        }
+    }
+
+    /// Build the `crank_distribute_page` instruction with explicit remaining accounts
+    pub async fn crank_ix_with_remaining(
+        &mut self,
+        page_index: u32,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        // Receipt of the page for the day in progress; a crank opening a new
+        // day runs a snapshot page, which does not use it
+        let progress: ProgressAccount = get_account(&mut self.context, self.progress_pda).await;
+        let mut accounts = damm_honorary_fee::accounts::CrankDistributePage {
+            policy_pda: self.policy_pda,
+            honorary_position: self.honorary_position_pda,
+            progress_pda: self.progress_pda,
+            roster_page: self.roster_page(page_index),
+            program_quote_treasury_ata: self.program_quote_treasury_ata,
+            program_base_treasury_ata: self.program_base_treasury_ata,
+            creator_quote_ata: self.creator_quote_ata,
+            investor_fee_position_owner_pda: self.owner_pda,
+            vault_pubkey: self.vault_pubkey,
+            quote_mint: self.quote_mint,
+            base_mint: self.base_mint,
+            pool: self.pool,
+            pool_authority: Pubkey::find_program_address(&[b"pool_authority"], &cp_amm_mock::ID).0,
+            position: self.position,
+            position_nft_account: self.position_nft_account,
+            token_a_vault: self.token_a_vault,
+            token_b_vault: self.token_b_vault,
+            token_a_mint: self.token_a_mint,
+            token_b_mint: self.token_b_mint,
+            event_authority: Pubkey::find_program_address(&[b"__event_authority"], &cp_amm_mock::ID).0,
+            cp_amm_program: cp_amm_mock::ID,
+            token_program: spl_token::ID,
+            page_receipt: self.page_receipt(progress.day_seq, page_index),
+            payer: self.context.payer.pubkey(),
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(remaining_accounts);
+
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts,
+            data: damm_honorary_fee::instruction::CrankDistributePage { page_index }.data(),
+        }
+    }
+
+    /// Dust ledger PDA for an investor stream
+    pub fn dust_ledger(&self, stream_pubkey: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"dust_ledger", self.policy_pda.as_ref(), stream_pubkey.as_ref()],
+            &damm_honorary_fee::ID,
+        )
+        .0
+    }
+
+    /// Investor roster page PDA
+    pub fn roster_page(&self, page_index: u32) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"roster", self.policy_pda.as_ref(), &page_index.to_le_bytes()],
+            &damm_honorary_fee::ID,
+        )
+        .0
+    }
+
+    /// Roster entry marker PDA of a stream
+    pub fn roster_entry(&self, stream_pubkey: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"roster_entry", self.policy_pda.as_ref(), stream_pubkey.as_ref()],
+            &damm_honorary_fee::ID,
+        )
+        .0
+    }
+
+    /// Set the investor roster to the given pages, one roster page per
+    /// investor page, through the roster instructions signed by the authority
+    ///
+    /// Pages already listing the given investors are left untouched, so their
+    /// markers and dust ledgers survive running another day over them.
+    pub async fn set_roster(&mut self, pages: Vec<Vec<InvestorAccount>>) {
+        let authority = self.creator_wallet.pubkey();
+        let policy: PolicyAccount = get_account(&mut self.context, self.policy_pda).await;
+        let mut current = Vec::new();
+        for page_index in 0..policy.roster_page_count {
+            let page_pda = self.roster_page(page_index);
+            let roster_page: InvestorRoster = get_account(&mut self.context, page_pda).await;
+            current.push(roster_page.entries);
+        }
+
+        // Empty changed and surplus pages first, so investors can move between pages
+        let mut ixs = Vec::new();
+        for (page_index, entries) in current.iter().enumerate() {
+            if pages.get(page_index) != Some(entries) {
+                for entry in entries {
+                    ixs.push(self.remove_roster_entry_ix(authority, page_index as u32, entry.stream_pubkey));
+                }
+            }
+        }
+        for page_index in (pages.len()..current.len()).rev() {
+            ixs.push(self.remove_roster_page_ix(authority, page_index as u32));
+        }
+        for (page_index, entries) in pages.iter().enumerate() {
+            match current.get(page_index) {
+                Some(listed) if listed == entries => continue,
+                Some(_) => {}
+                None => ixs.push(self.initialize_roster_page_ix(authority, page_index as u32)),
+            }
+            for entry in entries {
+                ixs.push(self.add_roster_entry_ix(authority, page_index as u32, entry.clone()));
+            }
+        }
+
+        for batch in ixs.chunks(4) {
+            process(&mut self.context, batch, &[&self.creator_wallet]).await.unwrap();
+        }
+    }
+
+    /// Write the investor roster and its entry markers directly, one roster
+    /// page per investor page, for rosters the roster instructions refuse to build
+    pub async fn write_roster(&mut self, pages: Vec<Vec<InvestorAccount>>) {
+        let mut policy: PolicyAccount = get_account(&mut self.context, self.policy_pda).await;
+        policy.roster_page_count = pages.len() as u32;
+        self.set_policy(&policy).await;
+
+        for (page_index, entries) in pages.into_iter().enumerate() {
+            let page_index = page_index as u32;
+            for entry in &entries {
+                let (address, bump) = Pubkey::find_program_address(
+                    &[b"roster_entry", self.policy_pda.as_ref(), entry.stream_pubkey.as_ref()],
+                    &damm_honorary_fee::ID,
+                );
+                let marker = RosterEntry {
+                    policy_id: self.policy_pda,
+                    stream_pubkey: entry.stream_pubkey,
+                    page_index,
+                    bump,
+                };
+                set_anchor_account(&mut self.context, address, damm_honorary_fee::ID, &marker, RosterEntry::LEN);
+            }
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"roster", self.policy_pda.as_ref(), &page_index.to_le_bytes()],
+                &damm_honorary_fee::ID,
+            );
+            let roster_page = InvestorRoster {
+                policy_id: self.policy_pda,
+                page_index,
+                entries,
+                bump,
+                snapshot_day_seq: 0,
+                snapshot_locked: vec![],
+                receipt_bump: 0,
+            };
+            set_anchor_account(&mut self.context, address, damm_honorary_fee::ID, &roster_page, InvestorRoster::LEN);
+        }
+    }
+
+    /// Page receipt PDA for a day and page
+    pub fn page_receipt(&self, day_seq: u64, page_index: u32) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"page_receipt",
+                self.policy_pda.as_ref(),
+                &day_seq.to_le_bytes(),
+                &page_index.to_le_bytes(),
+            ],
+            &damm_honorary_fee::ID,
+        )
+        .0
+    }
+
+    /// Build the `initialize_dust_ledger` instruction for an investor
+    pub fn initialize_dust_ledger_ix(&self, investor: &InvestorAccount) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::InitializeDustLedger {
+                policy_pda: self.policy_pda,
+                stream: investor.stream_pubkey,
+                roster_entry: self.roster_entry(investor.stream_pubkey),
+                dust_ledger: self.dust_ledger(investor.stream_pubkey),
+                payer: self.context.payer.pubkey(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::InitializeDustLedger {}.data(),
+        }
+    }
+
+    /// Create the dust ledger for an investor listed on the roster
+    pub async fn initialize_dust_ledger(&mut self, investor: &InvestorAccount) {
+        let ix = self.initialize_dust_ledger_ix(investor);
+        process(&mut self.context, &[ix], &[]).await.unwrap();
+    }
+
+    /// Build the `crank_distribute_page` instruction, passing the quote ATA,
+    /// stream and dust ledger of each investor on the roster page
+    pub async fn crank_ix(&mut self, page_index: u32) -> Instruction {
+        let entries = match self.context.banks_client.get_account(self.roster_page(page_index)).await.unwrap() {
+            Some(account) => InvestorRoster::try_deserialize(&mut account.data.as_ref()).unwrap().entries,
+            None => vec![],
+        };
+        let remaining_accounts = entries
+            .iter()
+            .flat_map(|investor| {
+                [
+                    AccountMeta::new(investor.investor_quote_ata, false),
+                    AccountMeta::new_readonly(investor.stream_pubkey, false),
+                    AccountMeta::new(self.dust_ledger(investor.stream_pubkey), false),
+                ]
+            })
+            .collect();
+        self.crank_ix_with_remaining(page_index, remaining_accounts).await
+    }
+
+    /// Run one crank page
+    pub async fn crank(&mut self, page_index: u32) -> Result<(), TransactionError> {
+        let ix = self.crank_ix(page_index).await;
+        process(&mut self.context, &[ix], &[]).await
+    }
+
+    /// Set the roster to the given investor pages and run both passes of a day over it
+    pub async fn run_day(&mut self, pages: Vec<Vec<InvestorAccount>>) -> Result<(), TransactionError> {
+        let page_count = pages.len() as u32;
+        self.set_roster(pages).await;
+        for _pass in 0..2 {
+            for page_index in 0..page_count {
+                self.crank(page_index).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Build an `update_policy` instruction signed by `authority`
+    pub fn update_policy_ix(&self, authority: Pubkey, params: PolicyParams) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdatePolicy {
+                policy_pda: self.policy_pda,
+                authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::UpdatePolicy { params }.data(),
+        }
+    }
+
+    /// Build a `propose_authority` instruction signed by `authority`
+    pub fn propose_authority_ix(&self, authority: Pubkey, new_authority: Pubkey) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdatePolicy {
+                policy_pda: self.policy_pda,
+                authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::ProposeAuthority { new_authority }.data(),
+        }
+    }
+
+    /// Build an `accept_authority` instruction signed by `pending_authority`
+    pub fn accept_authority_ix(&self, pending_authority: Pubkey) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::AcceptAuthority {
+                policy_pda: self.policy_pda,
+                pending_authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::AcceptAuthority {}.data(),
+        }
+    }
+
+    /// Build a `renounce_authority` instruction signed by `authority`
+    pub fn renounce_authority_ix(&self, authority: Pubkey) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdatePolicy {
+                policy_pda: self.policy_pda,
+                authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::RenounceAuthority {}.data(),
+        }
+    }
+
+    /// Build a `set_guardian` instruction signed by `authority`
+    pub fn set_guardian_ix(&self, authority: Pubkey, guardian: Option<Pubkey>) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdatePolicy {
+                policy_pda: self.policy_pda,
+                authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::SetGuardian { guardian }.data(),
+        }
+    }
+
+    /// Build a `set_paused` instruction signed by `signer`
+    pub fn set_paused_ix(&self, signer: Pubkey, paused: bool) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::SetPaused {
+                policy_pda: self.policy_pda,
+                signer,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::SetPaused { paused }.data(),
+        }
+    }
+
+    /// Build an `initialize_roster_page` instruction signed by `authority`
+    pub fn initialize_roster_page_ix(&self, authority: Pubkey, page_index: u32) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::InitializeRosterPage {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                roster_page: self.roster_page(page_index),
+                authority,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::InitializeRosterPage {}.data(),
+        }
+    }
+
+    /// Build an `add_roster_entry` instruction signed by `authority`
+    pub fn add_roster_entry_ix(&self, authority: Pubkey, page_index: u32, entry: InvestorAccount) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdateRoster {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                roster_page: self.roster_page(page_index),
+                roster_entry: self.roster_entry(entry.stream_pubkey),
+                dust_ledger: self.dust_ledger(entry.stream_pubkey),
+                authority,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::AddRosterEntry { page_index, entry }.data(),
+        }
+    }
+
+    /// Build a `remove_roster_entry` instruction signed by `authority`
+    pub fn remove_roster_entry_ix(&self, authority: Pubkey, page_index: u32, stream_pubkey: Pubkey) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::UpdateRoster {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                roster_page: self.roster_page(page_index),
+                roster_entry: self.roster_entry(stream_pubkey),
+                dust_ledger: self.dust_ledger(stream_pubkey),
+                authority,
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::RemoveRosterEntry { page_index, stream_pubkey }.data(),
+        }
+    }
+
+    /// Build a `remove_roster_page` instruction for the page at `page_index`,
+    /// signed by `authority`
+    pub fn remove_roster_page_ix(&self, authority: Pubkey, page_index: u32) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::RemoveRosterPage {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                roster_page: self.roster_page(page_index),
+                authority,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::RemoveRosterPage {}.data(),
+        }
+    }
+
+    /// Build the `close_page_receipt` instruction
+    pub fn close_page_receipt_ix(&self, page_receipt: Pubkey, payer: Pubkey) -> Instruction {
+        Instruction {
+            program_id: damm_honorary_fee::ID,
+            accounts: damm_honorary_fee::accounts::ClosePageReceipt {
+                policy_pda: self.policy_pda,
+                progress_pda: self.progress_pda,
+                page_receipt,
+                payer,
+            }
+            .to_account_metas(None),
+            data: damm_honorary_fee::instruction::ClosePageReceipt {}.data(),
+        }
+    }
+
+    /// Overwrite the policy account in place
+    pub async fn set_policy(&mut self, policy: &PolicyAccount) {
+        let space = self.context.banks_client.get_account(self.policy_pda).await.unwrap().unwrap().data.len();
+        set_anchor_account(&mut self.context, self.policy_pda, damm_honorary_fee::ID, policy, space);
+    }
+}
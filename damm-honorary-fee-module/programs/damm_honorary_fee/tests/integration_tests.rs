@@ -1,136 +1,1747 @@
 //! Integration tests for DAMM Honorary Fee Module
 //!
-//! These tests run against a local solana-test-validator and exercise
-//! the complete flow from initialization to fee distribution.
-
-use anchor_lang::prelude::*;
-use solana_program_test::*;
-use solana_sdk::{
-    instruction::Instruction,
-    signature::{Keypair, Signer},
-    transaction::Transaction,
-    pubkey::Pubkey,
-};
-
-use damm_honorary_fee::{
-    state::*,
-    events::*,
-};
-use damm_honorary_fee::damm_honorary_fee::*;
+//! These tests run against `solana-program-test` with a local cp-amm stand-in
+//! and exercise the complete flow from initialization to fee distribution.
+
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Signer};
+
+use damm_honorary_fee::{errors::DammHonoraryFeeError, state::*};
 
 mod helpers;
 
+use helpers::*;
+
 #[tokio::test]
 async fn test_initialize_honorary_position() {
-    let program_id = damm_honorary_fee::ID;
-    let mut context = setup_test_context().await;
-
-    // Generate test accounts
-    let pool_id = Pubkey::new_unique();
-    let vault_pubkey = Pubkey::new_unique();
-    let creator_wallet = Keypair::new();
-
-    // Create token mints (mock)
-    let quote_mint = Keypair::new();
-    let base_mint = Keypair::new();
-
-    // Create test context with accounts
-    let mut accounts = Vec::new();
-
-    // Add program accounts for initialization
-    let policy_pda = Pubkey::find_program_address(
-        &[b"policy", pool_id.as_ref()],
-        &program_id,
-    ).0;
-
-    let honorary_position_pda = Pubkey::find_program_address(
-        &[b"honorary_position", pool_id.as_ref()],
-        &program_id,
-    ).0;
-
-    let investor_fee_position_owner_pda = Pubkey::find_program_address(
-        &[b"vault", vault_pubkey.as_ref(), b"investor_fee_pos_owner"],
-        &program_id,
-    ).0;
-
-    // Create the initialize instruction
-    let initialize_ix = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new_readonly(pool_id, false),
-            AccountMeta::new_readonly(quote_mint.pubkey(), false),
-            AccountMeta::new_readonly(base_mint.pubkey(), false),
-            AccountMeta::new_readonly(honorary_position_pda, false),
-            AccountMeta::new_readonly(Pubkey::new_unique(), false), // position nft mint
-            AccountMeta::new(investor_fee_position_owner_pda, false),
-            AccountMeta::new_readonly(vault_pubkey, false),
-            AccountMeta::new_readonly(creator_wallet.pubkey(), true),
-            AccountMeta::new(policy_pda, false),
-            AccountMeta::new(honorary_position_pda, false),
-            AccountMeta::new(Pubkey::new_unique(), false), // program quote treasury ata
-            AccountMeta::new_readonly(quote_mint.pubkey(), false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-        ],
-        data: [
-            0, // discriminator for initialize_honorary_position
-            // TODO: Properly serialize the instruction data
-        ].to_vec(),
-    };
-
-    // Process the transaction
-    let tx = Transaction::new_signed(
-        &creator_wallet,
-        &[initialize_ix],
-        context.last_blockhash,
-        context.payer.pubkey(),
-    );
-
-    // This should succeed
-    assert!(context.banks_client.process_transaction(tx).await.is_ok());
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
 
     // Verify the accounts were created correctly
-    let policy_account = context.banks_client.get_account(policy_pda).await.unwrap().unwrap();
-    let policy_data: PolicyAccount = PolicyAccount::try_deserialize(&mut policy_account.data.as_ref()).unwrap();
+    let policy_data: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
 
-    assert_eq!(policy_data.pool_id, pool_id);
-    assert_eq!(policy_data.vault_pubkey, vault_pubkey);
-    assert_eq!(policy_data.creator_wallet, creator_wallet.pubkey());
-    assert_eq!(policy_data.quote_mint, quote_mint.pubkey());
+    assert_eq!(policy_data.pool_id, env.pool);
+    assert_eq!(policy_data.vault_pubkey, env.vault_pubkey);
+    assert_eq!(policy_data.creator_wallet, env.creator_wallet.pubkey());
+    assert_eq!(policy_data.quote_mint, env.quote_mint);
     assert_eq!(policy_data.investor_fee_share_bps, 5000);
     assert_eq!(policy_data.daily_cap_lamports, Some(1_000_000_000));
     assert_eq!(policy_data.min_payout_lamports, 100_000);
     assert_eq!(policy_data.y0_total_allocation, 1_000_000_000);
+
+    let position_data: HonoraryPositionAccount =
+        get_account(&mut env.context, env.honorary_position_pda).await;
+    assert_eq!(position_data.position_id, env.position);
+    assert_eq!(position_data.owner_pda, env.owner_pda);
+
+    for (address, len) in [
+        (env.policy_pda, PolicyAccount::LEN),
+        (env.honorary_position_pda, HonoraryPositionAccount::LEN),
+    ] {
+        let account = env.context.banks_client.get_account(address).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), len);
+    }
+
+    // The position was created in the pool with its NFT held by the owner PDA
+    let position: cp_amm_mock::Position = get_account(&mut env.context, env.position).await;
+    assert_eq!(position.pool, env.pool);
+    assert_eq!(position.nft_mint, env.position_nft_mint.pubkey());
+    assert_eq!(token_balance(&mut env.context, env.position_nft_account).await, 1);
+
+    // cp-amm mints position NFTs with Token-2022
+    let nft_account = env.context.banks_client.get_account(env.position_nft_account).await.unwrap().unwrap();
+    assert_eq!(nft_account.owner, anchor_spl::token_2022::ID);
+}
+
+#[tokio::test]
+async fn test_initialize_validates_accounts_and_arguments() {
+    let mut env = TestEnv::new().await;
+
+    // Pool not owned by cp-amm, with the PDAs derived from it
+    let fake_pool = env.funder_quote;
+    let mut ix = env.initialize_ix_with(|args| args.pool_id = fake_pool);
+    ix.accounts[0].pubkey = fake_pool;
+    for (meta, seed) in [(9, b"policy".as_ref()), (10, b"honorary_position".as_ref())] {
+        ix.accounts[meta].pubkey =
+            Pubkey::find_program_address(&[seed, fake_pool.as_ref()], &damm_honorary_fee::ID).0;
+    }
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPoolAccount)));
+
+    // Pool token mints that are not SPL mints
+    let invalid_pool_mint = Some(error_code(DammHonoraryFeeError::InvalidPoolMint));
+    let mut ix = env.initialize_ix();
+    ix.accounts[1].pubkey = env.funder_quote;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), invalid_pool_mint);
+
+    let mut ix = env.initialize_ix();
+    ix.accounts[2].pubkey = env.pool;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), invalid_pool_mint);
+
+    // Quote and base mint accounts that do not exist or are not mints
+    let mut ix = env.initialize_ix();
+    ix.accounts[11].pubkey = Pubkey::new_unique();
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidQuoteMint)));
+
+    let mut ix = env.initialize_ix();
+    ix.accounts[12].pubkey = env.funder_quote;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidBaseMint)));
+
+    // Base mint that is a mint, but not the pool's base token
+    let foreign_mint = create_mint(&mut env.context, 6).await;
+    let mut ix = env.initialize_ix();
+    ix.accounts[12].pubkey = foreign_mint;
+    ix.accounts[14].pubkey =
+        spl_associated_token_account::get_associated_token_address(&env.owner_pda, &foreign_mint);
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidBaseMint)));
+
+    // Treasury accounts that are not the owner PDA's ATAs
+    let mut ix = env.initialize_ix();
+    ix.accounts[13].pubkey = env.funder_quote;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidTreasuryAta)));
+
+    let mut ix = env.initialize_ix();
+    ix.accounts[14].pubkey = env.creator_quote_ata;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidTreasuryAta)));
+
+    // Arguments that disagree with the accounts
+    let ix = env.initialize_ix_with(|args| args.pool_id = Pubkey::new_unique());
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPoolId)));
+
+    let ix = env.initialize_ix_with(|args| args.vault_pubkey = Pubkey::new_unique());
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidVaultPubkey)));
+
+    let ix = env.initialize_ix_with(|args| args.investor_fee_share_bps = 10_001);
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidFeeShareBps)));
+
+    // Quote and base mint accounts swapped relative to the identified quote mint
+    let mut ix = env.initialize_ix();
+    for meta in ix.accounts.iter_mut().skip(3) {
+        if meta.pubkey == env.quote_mint {
+            meta.pubkey = env.base_mint;
+        } else if meta.pubkey == env.base_mint {
+            meta.pubkey = env.quote_mint;
+        } else if meta.pubkey == env.program_quote_treasury_ata {
+            meta.pubkey = env.program_base_treasury_ata;
+        } else if meta.pubkey == env.program_base_treasury_ata {
+            meta.pubkey = env.program_quote_treasury_ata;
+        }
+    }
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidQuoteMint)));
+
+    let ix = env.initialize_ix_with(|args| args.investor_fee_share_bps = 10_000);
+    env.process_initialize(ix).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_initialize_rejects_existing_position() {
+    let mut env = TestEnv::new().await;
+
+    // A position created outside the program cannot be registered
+    let (position, position_nft_account) = env.create_position(Pubkey::new_unique()).await;
+    env.position = position;
+    env.position_nft_account = position_nft_account;
+    let ix = env.initialize_ix();
+    assert!(env.process_initialize(ix).await.is_err());
+
+    let policy = env.context.banks_client.get_account(env.policy_pda).await.unwrap();
+    assert!(policy.is_none());
+}
+
+#[tokio::test]
+async fn test_known_quote_mint_as_token_b() {
+    let mut context = create_test_context().await;
+    let usdc = damm_honorary_fee::quote_mints::usdc::ID;
+    create_mint_at(&mut context, usdc, 6);
+    let base_mint = create_mint(&mut context, 6).await;
+
+    let mut env = TestEnv::with_pool(context, usdc, base_mint, false).await;
+    env.quote_mint_rule = QuoteMintRule::KnownQuoteMints;
+    env.initialize().await;
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.quote_mint, usdc);
+
+    // The crank claims token B as quote
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+}
+
+#[tokio::test]
+async fn test_quote_mint_identification_rejects_mismatches() {
+    let mut env = TestEnv::new().await;
+    let expected = Some(error_code(DammHonoraryFeeError::InvalidPoolTokenOrder));
+
+    // Neither pool token is a known quote mint
+    env.quote_mint_rule = QuoteMintRule::KnownQuoteMints;
+    let ix = env.initialize_ix();
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), expected);
+
+    // Explicit quote mint outside the pool
+    env.quote_mint_rule = QuoteMintRule::Explicit(Pubkey::new_unique());
+    let ix = env.initialize_ix();
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), expected);
+
+    // Token mints passed in the opposite order to the pool's
+    env.quote_mint_rule = QuoteMintRule::Explicit(env.quote_mint);
+    let mut ix = env.initialize_ix();
+    ix.accounts[1].pubkey = env.token_b_mint;
+    ix.accounts[2].pubkey = env.token_a_mint;
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), expected);
+
+    let ix = env.initialize_ix();
+    env.process_initialize(ix).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_initialize_progress() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.policy_id, env.policy_pda);
+    assert_eq!(progress.day_id, 0);
+    assert_eq!(progress.cursor_idx, 0);
+    assert_eq!(progress.day_state, DayState::Idle);
+    assert!(progress.paid_pages.iter().all(|byte| *byte == 0));
+
+    let account = env.context.banks_client.get_account(env.progress_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProgressAccount::LEN);
+
+    // The progress PDA can only be created once per policy
+    let ix = env.initialize_progress_ix();
+    assert!(process(&mut env.context, &[ix], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_crank_persists_progress() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice], vec![]]).await;
+    env.crank(0).await.unwrap();
+
+    let now = current_time(&mut env.context).await;
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, (now / 86400) as u64);
+    assert_eq!(progress.phase, DistributionPhase::Snapshot);
+    assert_eq!(progress.cursor_idx, 1);
+    assert_eq!(progress.claimed_quote_today, 1_000_000);
+    assert_eq!(progress.locked_total_today, 500_000_000);
+    assert_eq!(progress.cumulative_distributed_today, 0);
+
+    env.crank(1).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.phase, DistributionPhase::Payout);
+    assert_eq!(progress.cursor_idx, 0);
+    assert_eq!(progress.investor_pool_today, 500_000);
+
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.cursor_idx, 1);
+    assert_eq!(progress.locked_paid_today, 500_000_000);
+    assert_eq!(progress.cumulative_distributed_today, 500_000);
+    assert!(progress.is_page_paid(0));
+    assert!(!progress.is_page_paid(1));
+}
+
+#[tokio::test]
+async fn test_day_state_transitions() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let day_gate = Some(error_code(DammHonoraryFeeError::DayGateNotOpen));
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice], vec![bob]]).await;
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Idle);
+
+    // Idle -> Open on the first page; later pages of the open day proceed
+    env.crank(0).await.unwrap();
+    let today = (current_time(&mut env.context).await / 86400) as u64;
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Open);
+    assert_eq!(progress.day_id, today);
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // Open -> Closed on the final payout page; the closed day rejects pages
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Closed);
+    assert_eq!(progress.cursor_idx, 2);
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), day_gate);
+    let err = env.crank(1).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), day_gate);
+
+    // Still closed up to the last second of the day
+    let now = current_time(&mut env.context).await;
+    let until_gate = (today as i64 + 1) * 86400 - now;
+    advance_time(&mut env.context, until_gate - 1).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), day_gate);
+
+    // Closed -> Open at the gate, with the cursor back at page 0
+    advance_time(&mut env.context, 1).await;
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Open);
+    assert_eq!(progress.day_id, today + 1);
+    assert_eq!(progress.cursor_idx, 1);
+    assert_eq!(progress.phase, DistributionPhase::Snapshot);
+}
+
+#[tokio::test]
+async fn test_gate_crossed_mid_payout_carries_unpaid_pool() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+
+    // The day is abandoned before bob's page is paid: his 200k share is
+    // carried into the next day rather than paid to the creator
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Open);
+    assert_eq!(progress.phase, DistributionPhase::Snapshot);
+    assert_eq!(progress.carried_in_today, 200_000);
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 720_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 280_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_gate_crossed_mid_snapshot_keeps_claim_and_carry_in() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.min_payout_lamports = 250_000;
+    env.set_policy(&policy).await;
+
+    // Bob's 200k share is below the minimum payout and is carried
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()], vec![bob.clone()]]).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+
+    // The next day is abandoned after its first snapshot page
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.carried_in_today, 200_000);
+
+    // Its claim and carry-in are distributed by the day that replaces it
+    advance_time(&mut env.context, 86400).await;
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.claimed_quote_today, 1_000_000);
+    assert_eq!(progress.carried_in_today, 200_000);
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 720_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 280_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_configurable_epoch_length() {
+    const HOUR: i64 = 3600;
+    const WEEK: i64 = 7 * 86400;
+    let mut env = TestEnv::new().await;
+    let epoch_error = Some(error_code(DammHonoraryFeeError::InvalidEpochLength));
+    let day_gate = Some(error_code(DammHonoraryFeeError::DayGateNotOpen));
+
+    for (epoch_seconds, epoch_offset_seconds) in [(0, 0), (3600, 3600)] {
+        let ix = env.initialize_ix_with(|args| {
+            args.epoch_seconds = epoch_seconds;
+            args.epoch_offset_seconds = epoch_offset_seconds;
+        });
+        let err = env.process_initialize(ix).await.unwrap_err();
+        assert_eq!(custom_error_code(&err), epoch_error);
+    }
+
+    // Hourly epochs starting at half past; start 10s into an epoch at the start of a week
+    let now = current_time(&mut env.context).await;
+    let start = (now / WEEK + 1) * WEEK + HOUR / 2 + 10;
+    advance_time(&mut env.context, start - now).await;
+    env.initialize_with(|args| {
+        args.epoch_seconds = HOUR as u32;
+        args.epoch_offset_seconds = (HOUR / 2) as u32;
+    })
+    .await;
+    let first_epoch = ((start - HOUR / 2) / HOUR) as u64;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, first_epoch);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 500_000);
+
+    // The gate opens at the next half past, not at midnight
+    advance_time(&mut env.context, HOUR - 11).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), day_gate);
+    advance_time(&mut env.context, 1).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, first_epoch + 1);
+
+    // Switch to weekly epochs from the next hour on; the week already
+    // distributed in stays closed until the following week
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let weekly = PolicyParams {
+        epoch_seconds: WEEK as u32,
+        epoch_offset_seconds: 0,
+        ..policy.params()
+    };
+    let ix = env.update_policy_ix(env.creator_wallet.pubkey(), weekly);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    advance_time(&mut env.context, HOUR).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), day_gate);
+
+    let now = current_time(&mut env.context).await;
+    advance_time(&mut env.context, (now / WEEK + 1) * WEEK - now).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, (start / WEEK + 1) as u64);
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.params(), weekly);
+}
+
+#[tokio::test]
+async fn test_hundreds_of_pages_fit_progress_account() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // 300 pages per pass with investors on the first and last, batched per transaction
+    const PAGES: u32 = 300;
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    let page = |index: u32| match index {
+        0 => vec![alice.clone()],
+        i if i == PAGES - 1 => vec![bob.clone()],
+        _ => vec![],
+    };
+    env.set_roster((0..PAGES).map(page).collect()).await;
+    for _pass in 0..2 {
+        let mut ixs = Vec::new();
+        for i in 0..PAGES {
+            ixs.push(env.crank_ix(i).await);
+        }
+        for batch in ixs.chunks(10) {
+            process(&mut env.context, batch, &[]).await.unwrap();
+        }
+    }
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+
+    let account = env.context.banks_client.get_account(env.progress_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProgressAccount::LEN);
+    let mut progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Closed);
+    assert!((0..PAGES).all(|i| progress.is_page_paid(i)));
+    assert!(!progress.is_page_paid(PAGES));
+
+    // Pages beyond the bitmap are rejected rather than overflowing the account
+    env.write_roster(vec![vec![]; ProgressAccount::MAX_PAGES as usize + 1]).await;
+    progress.day_state = DayState::Open;
+    progress.cursor_idx = ProgressAccount::MAX_PAGES;
+    set_anchor_account(&mut env.context, env.progress_pda, damm_honorary_fee::ID, &progress, ProgressAccount::LEN);
+    let err = env.crank(ProgressAccount::MAX_PAGES).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::TooManyPages)));
 }
 
 #[tokio::test]
 async fn test_crank_distribute_page() {
-    // TODO: Implement crank distribution test
-    // This would involve:
-    // 1. Setting up a pool with fees
-    // 2. Creating an honorary position
-    // 3. Setting locked amounts in the mock Streamflow program
-    // 4. Calling the crank function
-    // 5. Verifying distributions
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    env.accrue_fees(1_000_000, 0).await;
+
+    env.set_roster(vec![vec![]]).await;
+    env.crank(0).await.unwrap();
+
+    // Claimed quote fees land in the program treasury
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.token_b_vault).await, 0);
+
+    let position: cp_amm_mock::Position = get_account(&mut env.context, env.position).await;
+    assert_eq!(position.fee_b_pending, 0);
 }
 
 #[tokio::test]
-async fn test_quote_only_validation() {
-    // TODO: Test that invalid tick ranges are rejected
+async fn test_crank_claims_nothing_without_fees() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    env.set_roster(vec![vec![]]).await;
+    env.crank(0).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
 }
 
 #[tokio::test]
-async fn test_pagination_idempotency() {
-    // TODO: Test that re-running the same page doesn't double-pay
+async fn test_crank_pays_investors_pro_rata() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // 500M of 1B still locked -> investors receive 50% of claimed fees
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    env.run_day(vec![vec![alice.clone(), bob.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_two_pass_distribution_across_pages() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // Each page's share depends on the locked total across all pages
+    let alice = env.create_investor(100_000_000).await;
+    let bob = env.create_investor(300_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // Fees accrued after the day opened are not claimed until the next day
+    env.accrue_fees(2_000_000, 0).await;
+
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // 400M of 1B locked -> 400k for investors, split 100:300
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 100_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 600_000);
+
+    let position: cp_amm_mock::Position = get_account(&mut env.context, env.position).await;
+    assert_eq!(position.fee_b_pending, 2_000_000);
+}
+
+#[tokio::test]
+async fn test_roster_defines_pages() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let authority = env.creator_wallet.pubkey();
+    let roster_locked = Some(error_code(DammHonoraryFeeError::RosterLocked));
+
+    let alice = env.create_investor(100_000_000).await;
+    let bob = env.create_investor(300_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    // Only the authority edits the roster, one page at a time
+    let stranger = solana_sdk::signature::Keypair::new();
+    let ixs = [
+        env.initialize_roster_page_ix(authority, 0),
+        env.add_roster_entry_ix(authority, 0, alice.clone()),
+        env.initialize_roster_page_ix(authority, 1),
+        env.add_roster_entry_ix(authority, 1, bob.clone()),
+    ];
+    process(&mut env.context, &ixs, &[&env.creator_wallet]).await.unwrap();
+    let ix = env.add_roster_entry_ix(stranger.pubkey(), 0, bob.clone());
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::Unauthorized)));
+    let ix = env.add_roster_entry_ix(authority, 0, alice.clone());
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::DuplicateRosterEntry)));
+    let ix = env.remove_roster_entry_ix(authority, 0, bob.stream_pubkey);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RosterEntryNotFound)));
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.roster_page_count, 2);
+    let page_1 = env.roster_page(1);
+    let roster_page: InvestorRoster = get_account(&mut env.context, page_1).await;
+    assert_eq!(roster_page.entries, vec![bob.clone()]);
+
+    // The roster is locked while the day is open, and the day only closes
+    // on the last roster page
+    env.crank(0).await.unwrap();
+    let ix = env.remove_roster_entry_ix(authority, 1, bob.stream_pubkey);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), roster_locked);
+    let ix = env.initialize_roster_page_ix(authority, 2);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), roster_locked);
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_state, DayState::Open);
+    env.crank(1).await.unwrap();
+
+    // 400M of 1B locked -> 400k for investors, split 100:300
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 100_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 600_000);
+
+    // Once the day closes the roster can change again
+    let ix = env.remove_roster_entry_ix(authority, 1, bob.stream_pubkey);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let roster_page: InvestorRoster = get_account(&mut env.context, page_1).await;
+    assert!(roster_page.entries.is_empty());
+
+    let ixs: Vec<_> = (0..InvestorRoster::MAX_ENTRIES)
+        .map(|_| {
+            let entry = InvestorAccount {
+                investor_quote_ata: Pubkey::new_unique(),
+                stream_pubkey: Pubkey::new_unique(),
+            };
+            env.add_roster_entry_ix(authority, 1, entry)
+        })
+        .collect();
+    process(&mut env.context, &ixs, &[&env.creator_wallet]).await.unwrap();
+    let ix = env.add_roster_entry_ix(authority, 1, bob.clone());
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RosterPageFull)));
+}
+
+#[tokio::test]
+async fn test_roster_lists_each_stream_once_and_shrinks() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let authority = env.creator_wallet.pubkey();
+
+    let alice = env.create_investor(100_000_000).await;
+    let bob = env.create_investor(300_000_000).await;
+    let ixs = [
+        env.initialize_roster_page_ix(authority, 0),
+        env.add_roster_entry_ix(authority, 0, alice.clone()),
+        env.initialize_roster_page_ix(authority, 1),
+        env.add_roster_entry_ix(authority, 1, bob.clone()),
+    ];
+    process(&mut env.context, &ixs, &[&env.creator_wallet]).await.unwrap();
+    let bob_marker = env.roster_entry(bob.stream_pubkey);
+    let marker: RosterEntry = get_account(&mut env.context, bob_marker).await;
+    assert_eq!(marker.page_index, 1);
+
+    // A stream listed on one page cannot be listed on another
+    let ix = env.add_roster_entry_ix(authority, 1, alice.clone());
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::DuplicateRosterEntry)));
+
+    // The marker must be the PDA of the stream being added
+    let carol = InvestorAccount {
+        investor_quote_ata: Pubkey::new_unique(),
+        stream_pubkey: Pubkey::new_unique(),
+    };
+    let mut ix = env.add_roster_entry_ix(authority, 1, carol);
+    ix.accounts[3].pubkey = env.roster_entry(alice.stream_pubkey);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidRosterEntry)));
+
+    // Only the last page can be removed, by the authority, once it is empty
+    let stranger = solana_sdk::signature::Keypair::new();
+    let ix = env.remove_roster_page_ix(stranger.pubkey(), 1);
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::Unauthorized)));
+    let ix = env.remove_roster_page_ix(authority, 1);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RosterPageNotEmpty)));
+
+    // Removing the entry closes its marker; the empty page is then closed
+    let ix = env.remove_roster_entry_ix(authority, 1, bob.stream_pubkey);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let marker = env.context.banks_client.get_account(bob_marker).await.unwrap();
+    assert!(marker.is_none());
+    let ix = env.remove_roster_page_ix(authority, 1);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.roster_page_count, 1);
+    let page = env.context.banks_client.get_account(env.roster_page(1)).await.unwrap();
+    assert!(page.is_none());
+
+    // The freed stream can be listed again
+    let ix = env.add_roster_entry_ix(authority, 0, bob.clone());
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let page_0 = env.roster_page(0);
+    let roster_page: InvestorRoster = get_account(&mut env.context, page_0).await;
+    assert_eq!(roster_page.entries, vec![alice, bob]);
+}
+
+#[tokio::test]
+async fn test_roster_lock_applies_due_policy_update() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let authority = env.creator_wallet.pubkey();
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+
+    // Two-day epochs from the next day on, aligned so that the open day's
+    // epoch under the new length has not ended a day later
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let params = PolicyParams {
+        epoch_seconds: 2 * 86400,
+        epoch_offset_seconds: (progress.last_opened_ts - 10).rem_euclid(2 * 86400) as u32,
+        ..policy.params()
+    };
+    let ix = env.update_policy_ix(authority, params);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    advance_time(&mut env.context, 86400).await;
+
+    // The update is due, so the crank still sees the day open: the roster
+    // stays locked
+    let carol = InvestorAccount {
+        investor_quote_ata: Pubkey::new_unique(),
+        stream_pubkey: Pubkey::new_unique(),
+    };
+    let ix = env.add_roster_entry_ix(authority, 1, carol);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RosterLocked)));
+
+    env.crank(1).await.unwrap();
+    let after: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(after.day_seq, progress.day_seq);
+    assert_eq!(after.phase, DistributionPhase::Payout);
+}
+
+#[tokio::test]
+async fn test_carry_drains_to_creator_once_fully_vested() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.min_payout_lamports = 600_000;
+    env.set_policy(&policy).await;
+
+    // A 500k share is below the minimum payout and is carried
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 500_000);
+
+    // Once the stream has fully vested nobody is locked: the carry-in is
+    // released with the day's claim to the creator
+    advance_time(&mut env.context, 400 * 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 2_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.locked_total_today, 0);
+    assert_eq!(progress.carried_in_today, 500_000);
+    assert_eq!(progress.carry_over_lamports, 0);
+}
+
+#[tokio::test]
+async fn test_final_page_pays_creator_remainder() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // 500M of 1B still locked -> investors receive 50% of claimed fees;
+    // the small investor's share is below the minimum payout and is carried
+    let alice = env.create_investor(499_900_000).await;
+    let dust = env.create_investor(100_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    env.run_day(vec![vec![alice.clone(), dust.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 499_900);
+    assert_eq!(token_balance(&mut env.context, dust.investor_quote_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 100);
+}
+
+#[tokio::test]
+async fn test_daily_cap_carries_excess_to_next_day() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.daily_cap_lamports = Some(200_000);
+    env.set_policy(&policy).await;
+
+    // 500k investor share clamped to 200k; 300k is held for tomorrow
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 300_000);
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.cumulative_distributed_today, 200_000);
+    assert_eq!(progress.carry_over_lamports, 300_000);
+
+    // The next day pays the held excess, again up to the cap
+    advance_time(&mut env.context, 86400).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 400_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 100_000);
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.carried_in_today, 300_000);
+    assert_eq!(progress.carry_over_lamports, 100_000);
+}
+
+#[tokio::test]
+async fn test_missed_days_distribute_as_one_multi_day_epoch() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.daily_cap_lamports = Some(400_000);
+    env.set_policy(&policy).await;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let first: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+
+    // Two days go by without a crank; the third distributes all three with
+    // the cap tripled: 1.5M investor share clamped to 1.2M
+    advance_time(&mut env.context, 3 * 86400).await;
+    env.accrue_fees(3_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, first.day_id + 3);
+    assert_eq!(progress.days_covered_today, 3);
+    assert_eq!(progress.carry_over_lamports, 300_000);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 1_200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 300_000);
+}
+
+#[tokio::test]
+async fn test_missed_days_replay_one_day_at_a_time() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.catch_up_mode = CatchUpMode::Replay;
+    env.set_policy(&policy).await;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let first: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+
+    // The fees claimed after two missed days are split evenly over the two
+    // replayed days and the current one
+    advance_time(&mut env.context, 3 * 86400).await;
+    env.accrue_fees(3_000_000, 0).await;
+    for replayed in 1..=3u64 {
+        env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+        let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+        assert_eq!(progress.day_id, first.day_id + replayed);
+        assert_eq!(progress.days_covered_today, 1);
+        assert_eq!(progress.claimed_quote_today, 1_000_000);
+        assert_eq!(progress.unassigned_claim_lamports, 3_000_000 - replayed * 1_000_000);
+        assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, replayed * 500_000);
+        assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, replayed * 500_000);
+        assert_eq!(
+            token_balance(&mut env.context, env.program_quote_treasury_ata).await,
+            progress.unassigned_claim_lamports,
+        );
+    }
+
+    // Caught up: the current day is closed until the next gate
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::DayGateNotOpen)));
+}
+
+#[tokio::test]
+async fn test_update_policy_requires_authority() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.authority, env.creator_wallet.pubkey());
+    let params = PolicyParams {
+        min_payout_lamports: 1,
+        ..policy.params()
+    };
+
+    let stranger = solana_sdk::signature::Keypair::new();
+    let ix = env.update_policy_ix(stranger.pubkey(), params);
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::Unauthorized)));
+
+    let invalid = PolicyParams {
+        investor_fee_share_bps: 10_001,
+        ..params
+    };
+    let ix = env.update_policy_ix(env.creator_wallet.pubkey(), invalid);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidFeeShareBps)));
+
+    // Accepted updates are only scheduled
+    let ix = env.update_policy_ix(env.creator_wallet.pubkey(), params);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let updated: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(updated.params(), policy.params());
+    assert_eq!(updated.pending_params, Some(params));
+    let today = (current_time(&mut env.context).await / 86400) as u64;
+    assert_eq!(updated.pending_effective_day_id, today + 1);
+}
+
+#[tokio::test]
+async fn test_policy_update_applies_next_day() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()]]).await;
+    env.crank(0).await.unwrap();
+
+    // Cap the pool and hand the remainder to a new creator mid-day
+    let new_creator = Pubkey::new_unique();
+    let new_creator_ata = create_token_account(&mut env.context, env.quote_mint, new_creator).await;
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let params = PolicyParams {
+        creator_wallet: new_creator,
+        daily_cap_lamports: Some(200_000),
+        ..policy.params()
+    };
+    let ix = env.update_policy_ix(env.creator_wallet.pubkey(), params);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    // The in-flight day finishes under the old parameters
+    env.crank(0).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+
+    // The next day runs under the new ones and rejects the old creator
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidCreatorAta)));
+
+    let old_creator_ata = env.creator_quote_ata;
+    env.creator_quote_ata = new_creator_ata;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 700_000);
+    assert_eq!(token_balance(&mut env.context, new_creator_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, old_creator_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 300_000);
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.params(), params);
+    assert_eq!(policy.pending_params, None);
+}
+
+#[tokio::test]
+async fn test_policy_update_replaces_pending_update() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let first = PolicyParams {
+        min_payout_lamports: 1,
+        ..policy.params()
+    };
+    let second = PolicyParams {
+        min_payout_lamports: 2,
+        ..policy.params()
+    };
+
+    // A second update before the effective day replaces the first one
+    let ixs = [
+        env.update_policy_ix(env.creator_wallet.pubkey(), first),
+        env.update_policy_ix(env.creator_wallet.pubkey(), second),
+    ];
+    process(&mut env.context, &ixs, &[&env.creator_wallet]).await.unwrap();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.pending_params, Some(second));
+
+    // Once it has applied, a further update leaves only itself pending
+    advance_time(&mut env.context, 86400).await;
+    let ix = env.update_policy_ix(env.creator_wallet.pubkey(), first);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.params(), second);
+    assert_eq!(policy.pending_params, Some(first));
 }
 
-async fn setup_test_context() -> ProgramTestContext {
-    let mut program_test = ProgramTest::new(
-        "damm_honorary_fee",
+#[tokio::test]
+async fn test_two_step_authority_transfer() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let unauthorized = Some(error_code(DammHonoraryFeeError::Unauthorized));
+
+    let creator = env.creator_wallet.pubkey();
+    let successor = solana_sdk::signature::Keypair::new();
+    let stranger = solana_sdk::signature::Keypair::new();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let params = PolicyParams {
+        min_payout_lamports: 1,
+        ..policy.params()
+    };
+
+    // Only the authority can propose
+    let ix = env.propose_authority_ix(stranger.pubkey(), stranger.pubkey());
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+
+    let ix = env.propose_authority_ix(creator, successor.pubkey());
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.authority, creator);
+    assert_eq!(policy.pending_authority, Some(successor.pubkey()));
+
+    // Only the proposed key can accept, and the old authority governs until it does
+    let ix = env.accept_authority_ix(stranger.pubkey());
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+    let ix = env.update_policy_ix(successor.pubkey(), params);
+    let err = process(&mut env.context, &[ix], &[&successor]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+
+    let ix = env.accept_authority_ix(successor.pubkey());
+    process(&mut env.context, &[ix], &[&successor]).await.unwrap();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(policy.authority, successor.pubkey());
+    assert_eq!(policy.pending_authority, None);
+
+    let ix = env.update_policy_ix(creator, params);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+    let ix = env.update_policy_ix(successor.pubkey(), params);
+    process(&mut env.context, &[ix], &[&successor]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_renounce_authority() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let unauthorized = Some(error_code(DammHonoraryFeeError::Unauthorized));
+
+    let creator = env.creator_wallet.pubkey();
+    let successor = solana_sdk::signature::Keypair::new();
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+
+    let ix = env.propose_authority_ix(creator, successor.pubkey());
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    let guardian = solana_sdk::signature::Keypair::new();
+    let ix = env.set_guardian_ix(creator, Some(guardian.pubkey()));
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    // Renouncing while paused would leave the crank halted for good
+    let ix = env.set_paused_ix(guardian.pubkey(), true);
+    process(&mut env.context, &[ix], &[&guardian]).await.unwrap();
+    let ix = env.renounce_authority_ix(creator);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RenounceWhilePaused)));
+    let ix = env.set_paused_ix(creator, false);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    let ix = env.renounce_authority_ix(creator);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let renounced: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    assert_eq!(renounced.authority, Pubkey::default());
+    assert_eq!(renounced.pending_authority, None);
+    assert_eq!(renounced.guardian, None);
+    assert!(!renounced.paused);
+
+    // Nobody can change the policy or take it over afterwards
+    let ix = env.update_policy_ix(creator, policy.params());
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+    let ix = env.propose_authority_ix(creator, creator);
+    let err = process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+    let ix = env.accept_authority_ix(successor.pubkey());
+    let err = process(&mut env.context, &[ix], &[&successor]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+
+    // The former guardian can no longer pause the crank
+    let ix = env.set_paused_ix(guardian.pubkey(), true);
+    let err = process(&mut env.context, &[ix], &[&guardian]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), unauthorized);
+}
+
+#[tokio::test]
+async fn test_pause_halts_crank_and_resumes_from_cursor() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let creator = env.creator_wallet.pubkey();
+    let guardian = solana_sdk::signature::Keypair::new();
+    let stranger = solana_sdk::signature::Keypair::new();
+    let ix = env.set_guardian_ix(creator, Some(guardian.pubkey()));
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    let ix = env.set_paused_ix(stranger.pubkey(), true);
+    let err = process(&mut env.context, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::Unauthorized)));
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+
+    // The guardian halts the crank mid-payout
+    let ix = env.set_paused_ix(guardian.pubkey(), true);
+    process(&mut env.context, &[ix], &[&guardian]).await.unwrap();
+    let err = env.crank(1).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::DistributionPaused)));
+
+    // Once resumed, paid pages cannot be replayed and the day completes
+    let ix = env.set_paused_ix(creator, false);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPaginationCursor)));
+    env.crank(1).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_pause_across_day_gate_resumes_open_day() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let creator = env.creator_wallet.pubkey();
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+    env.crank(0).await.unwrap();
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    let paused_day_id = progress.day_id;
+
+    // The pause lasts past the next day gate
+    let ix = env.set_paused_ix(creator, true);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    let ix = env.set_paused_ix(creator, false);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+
+    // The interrupted day continues from its cursor instead of being abandoned
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPaginationCursor)));
+    env.crank(1).await.unwrap();
+
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, paused_day_id);
+    assert_eq!(progress.day_state, DayState::Closed);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+
+    // The next day then opens right away and claims the fees accrued meanwhile
+    for page_index in [0, 1, 0, 1] {
+        env.crank(page_index).await.unwrap();
+    }
+
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.day_id, paused_day_id + 1);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 600_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 400_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_dust_rolls_into_next_day_pool() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.min_payout_lamports = 600_000;
+    env.set_policy(&policy).await;
+
+    // A 500k share is below the minimum payout and is carried
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 500_000);
+
+    // Tomorrow's 500k share plus the 500k carried in clears the minimum
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.carried_in_today, 500_000);
+    assert_eq!(progress.carry_over_lamports, 0);
+}
+
+#[tokio::test]
+async fn test_dust_ledger_batches_small_payouts() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // Bob's 80k daily share is below the 100k minimum payout
+    let alice = env.create_investor(420_000_000).await;
+    let bob = env.create_investor(80_000_000).await;
+    env.set_roster(vec![vec![alice.clone(), bob.clone()]]).await;
+    env.initialize_dust_ledger(&bob).await;
+    let bob_ledger = env.dust_ledger(bob.stream_pubkey);
+
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone(), bob.clone()]]).await.unwrap();
+
+    // The share accrues on the ledger and is held back from the creator
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 500_000);
+    let ledger: InvestorDustLedger = get_account(&mut env.context, bob_ledger).await;
+    assert_eq!(ledger.owed_lamports, 80_000);
+
+    // The second day's share pushes the owed total over the minimum
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone(), bob.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 160_000);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 840_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_000_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+    let ledger: InvestorDustLedger = get_account(&mut env.context, bob_ledger).await;
+    assert_eq!(ledger.owed_lamports, 0);
+
+    // Another investor's ledger cannot stand in for the investor's own
+    advance_time(&mut env.context, 86400).await;
+    env.crank(0).await.unwrap();
+    let ix = env.crank_ix_with_remaining(
+        0,
+        vec![
+            AccountMeta::new(alice.investor_quote_ata, false),
+            AccountMeta::new_readonly(alice.stream_pubkey, false),
+            AccountMeta::new(bob_ledger, false),
+            AccountMeta::new(bob.investor_quote_ata, false),
+            AccountMeta::new_readonly(bob.stream_pubkey, false),
+            AccountMeta::new(bob_ledger, false),
+        ],
+    )
+    .await;
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidDustLedger)));
+}
+
+#[tokio::test]
+async fn test_removing_investor_releases_dust_ledger() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    let authority = env.creator_wallet.pubkey();
+
+    // Only streams listed on the roster get a dust ledger
+    let alice = env.create_investor(420_000_000).await;
+    let bob = env.create_investor(80_000_000).await;
+    let ix = env.initialize_dust_ledger_ix(&bob);
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::RosterEntryNotFound)));
+
+    env.set_roster(vec![vec![alice.clone(), bob.clone()]]).await;
+    env.initialize_dust_ledger(&bob).await;
+    let bob_ledger = env.dust_ledger(bob.stream_pubkey);
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone(), bob.clone()]]).await.unwrap();
+    let ledger: InvestorDustLedger = get_account(&mut env.context, bob_ledger).await;
+    assert_eq!(ledger.owed_lamports, 80_000);
+
+    // Removing bob closes his ledger and releases what it still owes
+    let ix = env.remove_roster_entry_ix(authority, 0, bob.stream_pubkey);
+    process(&mut env.context, &[ix], &[&env.creator_wallet]).await.unwrap();
+    let ledger = env.context.banks_client.get_account(bob_ledger).await.unwrap();
+    assert!(ledger.is_none());
+    let progress: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(progress.ledger_dust_lamports, 0);
+
+    // The released dust reaches the creator with the next day's remainder
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 840_000);
+    assert_eq!(token_balance(&mut env.context, env.creator_quote_ata).await, 1_160_000);
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_crank_rejects_foreign_creator_ata() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let foreign_ata = create_token_account(&mut env.context, env.quote_mint, Pubkey::new_unique()).await;
+    env.creator_quote_ata = foreign_ata;
+    env.set_roster(vec![vec![]]).await;
+
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidCreatorAta)));
+}
+
+#[tokio::test]
+async fn test_crank_rejects_cross_wired_accounts() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![]]).await;
+    let seeds_code = Some(anchor_lang::error::ErrorCode::ConstraintSeeds as u32);
+
+    let policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    let honorary_position: HonoraryPositionAccount =
+        get_account(&mut env.context, env.honorary_position_pda).await;
+    let real = (env.policy_pda, env.honorary_position_pda, env.vault_pubkey, env.quote_mint, env.pool);
+
+    // Copy of the policy at an address that is not the pool's policy PDA
+    let copied_policy = Pubkey::new_unique();
+    set_anchor_account(&mut env.context, copied_policy, damm_honorary_fee::ID, &policy, PolicyAccount::LEN);
+    env.policy_pda = copied_policy;
+    env.write_roster(vec![vec![]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), seeds_code);
+    env.policy_pda = real.0;
+
+    // Policy of another pool
+    let other_pool = Pubkey::new_unique();
+    let other_policy =
+        Pubkey::find_program_address(&[b"policy", other_pool.as_ref()], &damm_honorary_fee::ID).0;
+    let other = PolicyAccount {
+        pool_id: other_pool,
+        ..policy.clone()
+    };
+    set_anchor_account(&mut env.context, other_policy, damm_honorary_fee::ID, &other, PolicyAccount::LEN);
+    env.policy_pda = other_policy;
+    env.write_roster(vec![vec![]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), seeds_code);
+
+    // ...and with that pool passed alongside, the honorary position no longer matches
+    env.pool = other_pool;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), seeds_code);
+    env.policy_pda = real.0;
+    env.pool = real.4;
+
+    // Policy PDA whose recorded pool disagrees with the pool passed
+    env.set_policy(&PolicyAccount {
+        pool_id: other_pool,
+        ..policy.clone()
+    })
+    .await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPoolId)));
+    env.set_policy(&policy).await;
+
+    // Copy of the honorary position at an address that is not its PDA
+    let copied_position = Pubkey::new_unique();
+    set_anchor_account(
+        &mut env.context,
+        copied_position,
         damm_honorary_fee::ID,
-        None,
+        &honorary_position,
+        HonoraryPositionAccount::LEN,
+    );
+    env.honorary_position_pda = copied_position;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), seeds_code);
+    env.honorary_position_pda = real.1;
+
+    // Vault whose owner PDA holds a treasury the policy does not govern
+    env.vault_pubkey = Pubkey::new_unique();
+    env.owner_pda = Pubkey::find_program_address(
+        &[b"vault", env.vault_pubkey.as_ref(), b"investor_fee_pos_owner"],
+        &damm_honorary_fee::ID,
+    )
+    .0;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidVaultPubkey)));
+    env.vault_pubkey = real.2;
+    env.owner_pda = honorary_position.owner_pda;
+
+    // Position NFT account of a position the owner PDA does not hold
+    let (_, foreign_nft_account) = env.create_position(Pubkey::new_unique()).await;
+    let real_nft_account = env.position_nft_account;
+    env.position_nft_account = foreign_nft_account;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPositionOwner)));
+    env.position_nft_account = real_nft_account;
+
+    // Quote mint and treasury swapped for the base side
+    env.quote_mint = env.base_mint;
+    std::mem::swap(&mut env.program_quote_treasury_ata, &mut env.program_base_treasury_ata);
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidQuoteMint)));
+    env.quote_mint = real.3;
+    std::mem::swap(&mut env.program_quote_treasury_ata, &mut env.program_base_treasury_ata);
+
+    // Base mint the policy was not created for
+    let real_base_mint = env.base_mint;
+    env.base_mint = create_mint(&mut env.context, 6).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidBaseMint)));
+    env.base_mint = real_base_mint;
+
+    // Quote treasury that is a quote token account, but not the owner PDA's ATA
+    let real_quote_treasury = env.program_quote_treasury_ata;
+    env.program_quote_treasury_ata = env.funder_quote;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidTreasuryAta)));
+    env.program_quote_treasury_ata = real_quote_treasury;
+
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+    env.crank(0).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_crank_rejects_mismatched_investor_ata() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(300_000_000).await;
+    let impostor = env.create_investor(300_000_000).await;
+    let base_ata = create_token_account(&mut env.context, env.base_mint, Pubkey::new_unique()).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    // ATA that differs from the roster entry
+    env.set_roster(vec![vec![alice.clone()]]).await;
+    let ix = env.crank_ix_with_remaining(
+        0,
+        vec![
+            AccountMeta::new(impostor.investor_quote_ata, false),
+            AccountMeta::new_readonly(alice.stream_pubkey, false),
+            AccountMeta::new(env.dust_ledger(alice.stream_pubkey), false),
+        ],
+    )
+    .await;
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidInvestorAta)));
+
+    // ATA for the wrong mint
+    let wrong_mint = InvestorAccount {
+        investor_quote_ata: base_ata,
+        ..alice.clone()
+    };
+    env.set_roster(vec![vec![wrong_mint]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidInvestorAta)));
+
+    // Missing stream and dust ledger accounts
+    env.set_roster(vec![vec![alice.clone()]]).await;
+    let ix = env.crank_ix_with_remaining(0, vec![AccountMeta::new(alice.investor_quote_ata, false)]).await;
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidInvestorAta)));
+
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_crank_reads_locked_amounts_from_streams() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    // 400M streamed over four days, two of which have elapsed -> 200M locked
+    let wallet = Pubkey::new_unique();
+    let investor_quote_ata = create_token_account(&mut env.context, env.quote_mint, wallet).await;
+    let start_time = current_time(&mut env.context).await as u64 - 2 * 86400 - 10;
+    let stream_pubkey = env
+        .create_stream(wallet, start_time, 400_000_000, 86400, 100_000_000)
+        .await;
+    let vesting = InvestorAccount {
+        investor_quote_ata,
+        stream_pubkey,
+    };
+    let locked = env.create_investor(300_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    env.run_day(vec![vec![vesting.clone(), locked.clone()]]).await.unwrap();
+
+    // 500M of 1B locked -> 500k for investors, split 200:300
+    assert_eq!(token_balance(&mut env.context, vesting.investor_quote_ata).await, 200_000);
+    assert_eq!(token_balance(&mut env.context, locked.investor_quote_ata).await, 300_000);
+}
+
+#[tokio::test]
+async fn test_stream_canceled_between_passes_pays_from_snapshot() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(300_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // Canceled after the snapshot: the payout still uses the snapshot amounts
+    advance_time(&mut env.context, 60).await;
+    env.cancel_stream(alice.stream_pubkey).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // 600M of 1B locked -> 500k for investors, split evenly
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 250_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 250_000);
+
+    // From the next day on the canceled stream counts as unlocked
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()], vec![bob.clone()]]).await.unwrap();
+
+    // 300M of 1B locked -> 300k, all to bob
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 250_000);
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 550_000);
+}
+
+#[tokio::test]
+async fn test_crank_rejects_mismatched_streams() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(300_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+
+    // Another investor's stream in place of the roster entry's
+    env.set_roster(vec![vec![alice.clone()]]).await;
+    let ix = env.crank_ix_with_remaining(
+        0,
+        vec![
+            AccountMeta::new(alice.investor_quote_ata, false),
+            AccountMeta::new_readonly(bob.stream_pubkey, false),
+            AccountMeta::new(env.dust_ledger(alice.stream_pubkey), false),
+        ],
+    )
+    .await;
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::StreamflowReadError)));
+
+    // A stream vesting to someone other than the ATA owner
+    let hijacked = InvestorAccount {
+        investor_quote_ata: alice.investor_quote_ata,
+        stream_pubkey: bob.stream_pubkey,
+    };
+    env.set_roster(vec![vec![hijacked]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::StreamflowReadError)));
+
+    // An account not owned by Streamflow
+    let forged = InvestorAccount {
+        investor_quote_ata: alice.investor_quote_ata,
+        stream_pubkey: env.funder_quote,
+    };
+    env.set_roster(vec![vec![forged]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::StreamflowReadError)));
+
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+}
+
+#[tokio::test]
+async fn test_crank_aborts_on_base_fees() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    env.accrue_fees(1_000_000, 1).await;
+
+    env.set_roster(vec![vec![]]).await;
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(
+        custom_error_code(&err),
+        Some(error_code(DammHonoraryFeeError::BaseFeesObserved))
     );
 
-    program_test.start().await
-}
\ No newline at end of file
+    // Nothing was claimed: the page reverted atomically
+    assert_eq!(token_balance(&mut env.context, env.program_quote_treasury_ata).await, 0);
+    assert_eq!(token_balance(&mut env.context, env.program_base_treasury_ata).await, 0);
+    let position: cp_amm_mock::Position = get_account(&mut env.context, env.position).await;
+    assert_eq!(position.fee_a_pending, 1);
+    assert_eq!(position.fee_b_pending, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_quote_only_validation() {
+    let mut env = TestEnv::new().await;
+
+    // The pool must charge every fee in token B, the quote token
+    env.set_pool_collect_fee_mode(0).await;
+    let ix = env.initialize_ix();
+    let err = env.process_initialize(ix).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::NotQuoteOnly)));
+    env.set_pool_collect_fee_mode(1).await;
+
+    // Quote is token B: the price must sit at the top of the pool's range
+    for (sqrt_min_price, sqrt_max_price, sqrt_price, expected) in [
+        (POOL_SQRT_MIN_PRICE, POOL_SQRT_MAX_PRICE, POOL_SQRT_MAX_PRICE - 1, DammHonoraryFeeError::NotQuoteOnly),
+        (POOL_SQRT_MIN_PRICE, POOL_SQRT_MAX_PRICE, POOL_SQRT_MIN_PRICE, DammHonoraryFeeError::NotQuoteOnly),
+        (POOL_SQRT_MAX_PRICE, POOL_SQRT_MIN_PRICE, POOL_SQRT_MIN_PRICE, DammHonoraryFeeError::InvalidTickRange),
+        (POOL_SQRT_MIN_PRICE, u128::MAX, u128::MAX, DammHonoraryFeeError::InvalidTickRange),
+    ] {
+        env.set_pool_price(sqrt_min_price, sqrt_max_price, sqrt_price).await;
+        let ix = env.initialize_ix();
+        let err = env.process_initialize(ix).await.unwrap_err();
+        assert_eq!(custom_error_code(&err), Some(error_code(expected)));
+    }
+
+    env.set_pool_price(POOL_SQRT_MIN_PRICE, POOL_SQRT_MAX_PRICE, POOL_SQRT_MAX_PRICE).await;
+    let ix = env.initialize_ix();
+    env.process_initialize(ix).await.unwrap();
+
+    let position: HonoraryPositionAccount = get_account(&mut env.context, env.honorary_position_pda).await;
+    assert_eq!(position.sqrt_min_price, POOL_SQRT_MIN_PRICE);
+    assert_eq!(position.sqrt_max_price, POOL_SQRT_MAX_PRICE);
+}
+
+#[tokio::test]
+async fn test_pagination_idempotency() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(300_000_000).await;
+    let bob = env.create_investor(200_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.set_roster(vec![vec![alice.clone()], vec![bob.clone()]]).await;
+    env.crank(0).await.unwrap();
+    env.crank(1).await.unwrap();
+
+    // Pay page 0, then roll the progress PDA back to before it was paid
+    let before: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    env.crank(0).await.unwrap();
+    let after: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+
+    let receipt_key = env.page_receipt(after.day_seq, 0);
+    let receipt: PageReceipt = get_account(&mut env.context, receipt_key).await;
+    assert_eq!(receipt.policy_id, env.policy_pda);
+    assert_eq!(receipt.page_index, 0);
+    assert_eq!(receipt.payer, env.context.payer.pubkey());
+    assert_eq!(receipt.paid_lamports, 300_000);
+
+    set_anchor_account(&mut env.context, env.progress_pda, damm_honorary_fee::ID, &before, ProgressAccount::LEN);
+    let err = env.crank(0).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::PageAlreadyPaid)));
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 300_000);
+    set_anchor_account(&mut env.context, env.progress_pda, damm_honorary_fee::ID, &after, ProgressAccount::LEN);
+
+    // Another page's receipt is rejected
+    let mut ix = env.crank_ix(1).await;
+    let page_1_receipt = env.page_receipt(after.day_seq, 1);
+    ix.accounts.iter_mut().filter(|meta| meta.pubkey == page_1_receipt).for_each(|meta| meta.pubkey = receipt_key);
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::InvalidPageReceipt)));
+
+    // Receipts stay open until the day closes, then refund their rent
+    let payer = env.context.payer.pubkey();
+    let ix = env.close_page_receipt_ix(receipt_key, payer);
+    let err = process(&mut env.context, &[ix], &[]).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(error_code(DammHonoraryFeeError::DayNotClosed)));
+
+    // Lamports sent to a receipt address beforehand do not block the page
+    let prefund = solana_sdk::system_instruction::transfer(&payer, &page_1_receipt, 1_000_000);
+    process(&mut env.context, &[prefund], &[]).await.unwrap();
+    env.crank(1).await.unwrap();
+    assert_eq!(token_balance(&mut env.context, bob.investor_quote_ata).await, 200_000);
+    let ixs = [
+        env.close_page_receipt_ix(receipt_key, payer),
+        env.close_page_receipt_ix(page_1_receipt, payer),
+    ];
+    process(&mut env.context, &ixs, &[]).await.unwrap();
+    for key in [receipt_key, page_1_receipt] {
+        assert!(env.context.banks_client.get_account(key).await.unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_receipts_survive_day_id_reuse_after_epoch_change() {
+    let mut env = TestEnv::new().await;
+    env.initialize().await;
+
+    let alice = env.create_investor(500_000_000).await;
+    env.accrue_fees(1_000_000, 0).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+    let first: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    let first_receipt = env.page_receipt(first.day_seq, 0);
+
+    // Shifting the epoch offset makes the next day reuse the previous day_id,
+    // while that day's receipt is still open
+    advance_time(&mut env.context, 86400).await;
+    env.accrue_fees(1_000_000, 0).await;
+    let now = current_time(&mut env.context).await;
+    let mut policy: PolicyAccount = get_account(&mut env.context, env.policy_pda).await;
+    policy.epoch_offset_seconds = (now - (first.day_id as i64 + 1) * 86400 + 1) as u32;
+    env.set_policy(&policy).await;
+    env.run_day(vec![vec![alice.clone()]]).await.unwrap();
+
+    let second: ProgressAccount = get_account(&mut env.context, env.progress_pda).await;
+    assert_eq!(second.day_id, first.day_id);
+    assert_eq!(second.day_seq, first.day_seq + 1);
+    assert_eq!(token_balance(&mut env.context, alice.investor_quote_ata).await, 1_000_000);
+    let receipt: PageReceipt = get_account(&mut env.context, first_receipt).await;
+    assert_eq!(receipt.day_seq, first.day_seq);
+    let second_receipt = env.page_receipt(second.day_seq, 0);
+    let receipt: PageReceipt = get_account(&mut env.context, second_receipt).await;
+    assert_eq!((receipt.day_id, receipt.day_seq), (second.day_id, second.day_seq));
+}
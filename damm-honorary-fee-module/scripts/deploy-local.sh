@@ -11,7 +11,8 @@ echo "🚀 Deploying DAMM Honorary Fee Module to local validator..."
 echo "🌐 Starting local Solana validator..."
 solana-test-validator \
   --reset \
-  --bpf-program StreamMock11111111111111111111111111111111 mock_programs/streamflow_mock/target/deploy/streamflow_mock.so \
+  --bpf-program strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m mock_programs/streamflow_mock/target/deploy/streamflow_mock.so \
+  --bpf-program cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG mock_programs/cp_amm_mock/target/deploy/cp_amm_mock.so \
   --quiet &
 VALIDATOR_PID=$!
 